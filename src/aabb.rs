@@ -0,0 +1,167 @@
+use crate::interval::{self, Interval};
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+pub const EMPTY: Aabb = Aabb {
+    x: interval::EMPTY,
+    y: interval::EMPTY,
+    z: interval::EMPTY,
+};
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        let mut bbox = Aabb { x, y, z };
+        bbox.pad_to_minimums();
+        bbox
+    }
+
+    pub fn from_points(a: Point3, b: Point3) -> Self {
+        // Treat the two points a and b as extrema for the bounding box, so we don't require a
+        // particular minimum/maximum coordinate order.
+        Aabb::new(
+            Interval::new(f64::min(a[0], b[0]), f64::max(a[0], b[0])),
+            Interval::new(f64::min(a[1], b[1]), f64::max(a[1], b[1])),
+            Interval::new(f64::min(a[2], b[2]), f64::max(a[2], b[2])),
+        )
+    }
+
+    pub fn surrounding(box0: Aabb, box1: Aabb) -> Self {
+        Aabb {
+            x: Interval::enclosing(box0.x, box1.x),
+            y: Interval::enclosing(box0.y, box1.y),
+            z: Interval::enclosing(box0.z, box1.z),
+        }
+    }
+
+    pub fn axis_interval(&self, n: usize) -> Interval {
+        match n {
+            1 => self.y,
+            2 => self.z,
+            _ => self.x,
+        }
+    }
+
+    pub fn hit(&self, r: Ray, mut ray_t: Interval) -> bool {
+        let ray_orig = r.origin();
+        let ray_dir = r.direction();
+
+        for axis in 0..3 {
+            let ax = self.axis_interval(axis);
+            let adinv = 1.0 / ray_dir[axis];
+
+            let t0 = (ax.min - ray_orig[axis]) * adinv;
+            let t1 = (ax.max - ray_orig[axis]) * adinv;
+
+            if t0 < t1 {
+                if t0 > ray_t.min {
+                    ray_t.min = t0;
+                }
+                if t1 < ray_t.max {
+                    ray_t.max = t1;
+                }
+            } else {
+                if t1 > ray_t.min {
+                    ray_t.min = t1;
+                }
+                if t0 < ray_t.max {
+                    ray_t.max = t0;
+                }
+            }
+
+            if ray_t.max <= ray_t.min {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        // Returns the index of the longest axis of the bounding box.
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() {
+                0
+            } else {
+                2
+            }
+        } else if self.y.size() > self.z.size() {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn pad_to_minimums(&mut self) {
+        // Adjust the AABB so that no side is narrower than some delta, padding if necessary.
+        let delta = 0.0001;
+        if self.x.size() < delta {
+            self.x = self.x.expand(delta);
+        }
+        if self.y.size() < delta {
+            self.y = self.y.expand(delta);
+        }
+        if self.z.size() < delta {
+            self.z = self.z.expand(delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_from_points() {
+        let bbox = Aabb::from_points(Point3::new(1.0, -2.0, 3.0), Point3::new(-1.0, 2.0, 0.0));
+        assert_eq!(bbox.x.min, -1.0);
+        assert_eq!(bbox.x.max, 1.0);
+        assert_eq!(bbox.y.min, -2.0);
+        assert_eq!(bbox.y.max, 2.0);
+        assert_eq!(bbox.z.min, 0.0);
+        assert_eq!(bbox.z.max, 3.0);
+    }
+
+    #[test]
+    fn test_surrounding() {
+        let box0 = Aabb::from_points(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let box1 = Aabb::from_points(Point3::new(2.0, -1.0, 0.5), Point3::new(3.0, 0.5, 0.6));
+        let bbox = Aabb::surrounding(box0, box1);
+        assert_eq!(bbox.x.min, 0.0);
+        assert_eq!(bbox.x.max, 3.0);
+        assert_eq!(bbox.y.min, -1.0);
+        assert_eq!(bbox.y.max, 1.0);
+        assert_eq!(bbox.longest_axis(), 0);
+
+        let bbox = Aabb::surrounding(EMPTY, box0);
+        assert_eq!(bbox.x.min, 0.0);
+        assert_eq!(bbox.x.max, 1.0);
+    }
+
+    #[test]
+    fn test_hit() {
+        let bbox = Aabb::from_points(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+
+        let r = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(bbox.hit(r, ray_t));
+
+        let r = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!bbox.hit(r, ray_t));
+
+        let r = Ray::new(Point3::new(0.0, 2.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(!bbox.hit(r, ray_t));
+    }
+
+    #[test]
+    fn test_pad_to_minimums() {
+        let bbox = Aabb::from_points(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 0.0));
+        assert!(bbox.z.size() > 0.0);
+    }
+}
@@ -0,0 +1,145 @@
+use std::cmp::Ordering;
+
+use crate::aabb::{self, Aabb};
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+
+pub struct BvhNode {
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(list: HittableList) -> Self {
+        let mut objects = list.into_objects();
+
+        match objects.len() {
+            // Pad with empty lists so that every node has two children; an empty list never
+            // reports a hit and has an empty bounding box.
+            0 => BvhNode::from_pair(Box::new(HittableList::new()), Box::new(HittableList::new())),
+            1 => BvhNode::from_pair(objects.remove(0), Box::new(HittableList::new())),
+            _ => BvhNode::build(objects),
+        }
+    }
+
+    fn from_pair(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        let bbox = Aabb::surrounding(left.bounding_box(), right.bounding_box());
+        BvhNode { left, right, bbox }
+    }
+
+    fn build(mut objects: Vec<Box<dyn Hittable>>) -> Self {
+        // Build the bounding box of the span of source objects.
+        let bbox = objects.iter().fold(aabb::EMPTY, |bbox, obj| {
+            Aabb::surrounding(bbox, obj.bounding_box())
+        });
+
+        // Split along the longest axis, at the median of the box minimums.
+        let axis = bbox.longest_axis();
+        objects.sort_by(|a, b| box_compare(a.as_ref(), b.as_ref(), axis));
+
+        let right_objects = objects.split_off(objects.len() / 2);
+        let left = BvhNode::subtree(objects);
+        let right = BvhNode::subtree(right_objects);
+
+        BvhNode { left, right, bbox }
+    }
+
+    fn subtree(mut objects: Vec<Box<dyn Hittable>>) -> Box<dyn Hittable> {
+        if objects.len() == 1 {
+            objects.remove(0)
+        } else {
+            Box::new(BvhNode::build(objects))
+        }
+    }
+}
+
+fn box_compare(a: &dyn Hittable, b: &dyn Hittable, axis_index: usize) -> Ordering {
+    let a_axis_interval = a.bounding_box().axis_interval(axis_index);
+    let b_axis_interval = b.bounding_box().axis_interval(axis_index);
+    a_axis_interval.min.total_cmp(&b_axis_interval.min)
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        if !self.bbox.hit(r, ray_t) {
+            return None;
+        }
+
+        let hit_left = self.left.hit(r, ray_t);
+        let max = hit_left.map_or(ray_t.max, |rec| rec.t);
+        let hit_right = self.right.hit(r, Interval::new(ray_t.min, max));
+
+        hit_right.or(hit_left)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.left.materials(visit);
+        self.right.materials(visit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+    use crate::vec3::{Point3, Vec3};
+
+    fn spheres() -> HittableList {
+        let mut world = HittableList::new();
+        for a in -5..5 {
+            for b in -5..5 {
+                let center = Point3::new(a as f64, 0.0, b as f64);
+                world.add(Sphere::new(
+                    center,
+                    0.3,
+                    Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+                ));
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn test_matches_list() {
+        let list = spheres();
+        let bvh = BvhNode::new(spheres());
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+
+        for i in -20..20 {
+            let origin = Point3::new(i as f64 * 0.25, 5.0, -7.0);
+            let r = Ray::new(origin, Vec3::new(0.1, -1.0, 1.0));
+
+            let expected = list.hit(r, ray_t).map(|rec| rec.t);
+            let actual = bvh.hit(r, ray_t).map(|rec| rec.t);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_small_lists() {
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+        let r = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+
+        let bvh = BvhNode::new(HittableList::new());
+        assert!(bvh.hit(r, ray_t).is_none());
+
+        let mut list = HittableList::new();
+        list.add(Sphere::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ));
+        let bvh = BvhNode::new(list);
+        assert_eq!(bvh.hit(r, ray_t).map(|rec| rec.t), Some(4.0));
+    }
+}
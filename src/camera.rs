@@ -1,32 +1,37 @@
 use core::f64;
-use std::sync::Arc;
+use std::error::Error;
+use std::fmt;
+use std::sync::{mpsc, Arc};
+use std::thread;
 
 use indicatif::ProgressBar;
 
-use crate::color;
 use crate::color::Color;
 use crate::hittable::Hittable;
-use crate::interval::Interval;
+use crate::image::Image;
+use crate::integrator::{Integrator, NaivePathTracer};
+use crate::material::MaterialNumbers;
 use crate::ray::Ray;
 
-use crate::rtweekend::{degrees_to_radians, random_f64};
+use crate::rtweekend::{degrees_to_radians, random_f64, random_f64_bounded, seeded_rng, Rng};
 use crate::vec3::{Point3, Vec3};
 use log::info;
-use std::thread;
+use serde::Deserialize;
 use threadpool::ThreadPool;
 
+#[derive(Clone)]
 pub struct Camera {
     pub image_width: i32,       // Rendered image width in pixel count
-    pub samples_per_pixel: i32, // Count of random samples for each pixel
+    pub samples_per_pixel: i32, // Count of random samples for each pixel, at most if adaptive
 
-    image_height: i32,        // Rendered image height
-    center: Point3,           // Camera center
-    pixel00_loc: Point3,      // Location of pixel 0, 0
-    pixel_delta_u: Vec3,      // Offset to pixel to the right
-    pixel_delta_v: Vec3,      // Offset to pixel below
-    pixel_samples_scale: f64, // Color scale factor for a sum of pixel samples
+    image_height: i32,   // Rendered image height
+    center: Point3,      // Camera center
+    pixel00_loc: Point3, // Location of pixel 0, 0
+    pixel_delta_u: Vec3, // Offset to pixel to the right
+    pixel_delta_v: Vec3, // Offset to pixel below
 
-    pub max_depth: i32, // Maximum number of ray bounces into scene
+    pub max_depth: i32,      // Maximum number of ray bounces into scene
+    pub roulette_depth: i32, // Bounces before Russian roulette may end dim paths
 
     pub vfov: i32,
 
@@ -41,12 +46,124 @@ pub struct Camera {
     // focus_dist: f64,    // Distance from camera lookfrom point to plane of perfect focus
     defocus_disk_u: Vec3, // Defocus disk horizontal radius
     defocus_disk_v: Vec3, // Defocus disk vertical radius
+
+    pub threads: usize, // Count of worker threads used by render
+    pub tile_size: i32, // Width and height of the square tiles the image is split into
+    pub seed: u64,      // Seed of the per-pixel random number generators
+
+    pub background: Option<Color>, // Color of rays leaving the scene, None for the sky gradient
+
+    pub shutter_open: f64, // Time at which the shutter opens, objects move from time 0 to 1
+    pub shutter_close: f64, // Time at which the shutter closes
+
+    pub fog: Option<Fog>, // Fog filling the whole scene, None for clear air
+
+    pub lights: Option<Arc<dyn Hittable>>, // Emitters sampled directly, e.g. the scene's lamps
+    pub integrator: Arc<dyn Integrator>,   // Algorithm estimating the light along camera rays
+
+    pub adaptive: Option<AdaptiveSampling>, // Stops sampling converged pixels, None for fixed counts
+
+    pub material_numbers: Arc<MaterialNumbers>, // Set by `render` for the world it draws
 }
 
-unsafe impl Sync for Camera {}
-// unsafe impl Send for Camera {}
+// Homogeneous fog for depth cueing: along each ray segment, the light reaching the eye fades
+// into the fog color as exp(-density * distance). Rays leaving the scene see only fog.
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fog {
+    pub density: f64,
+    pub color: Color,
+}
+
+// Adaptive sampling: after `min_samples`, a pixel stops taking samples once the 95% confidence
+// interval of its mean luminance is within `threshold` of it, relative to the luminance (or to
+// 0.01 for darker pixels, whose noise is hard to see anyway).
+// Samples that all agree show no variance, so a pixel whose first `min_samples` samples are all
+// black, e.g. because none of them found a small light, stops there and stays black. Raise
+// `min_samples` for scenes lit by lights that few paths reach.
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdaptiveSampling {
+    pub threshold: f64,
+    #[serde(default = "AdaptiveSampling::default_min_samples")]
+    pub min_samples: i32,
+}
+
+impl AdaptiveSampling {
+    pub fn new(threshold: f64) -> Self {
+        AdaptiveSampling {
+            threshold,
+            min_samples: AdaptiveSampling::default_min_samples(),
+        }
+    }
+
+    fn default_min_samples() -> i32 {
+        16
+    }
+
+    fn converged(&self, stats: &SampleStats) -> bool {
+        stats.count >= self.min_samples as f64
+            && stats.error() <= self.threshold * f64::max(stats.mean, 0.01)
+    }
+}
+
+// Running mean and variance of a pixel's sample luminances, with Welford's algorithm.
+#[derive(Default)]
+struct SampleStats {
+    count: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl SampleStats {
+    fn push(&mut self, color: Color) {
+        let x = 0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z();
+        self.count += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.count;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn error(&self) -> f64 {
+        // Half-width of the 95% confidence interval of the mean.
+        if self.count < 2.0 {
+            return f64::INFINITY;
+        }
+        let variance = self.m2 / (self.count - 1.0);
+        1.96 * f64::sqrt(variance / self.count)
+    }
+}
+
+// Error of a render whose image has more pixels than can be counted or held in memory.
+#[derive(Copy, Clone, Debug)]
+pub struct ImageTooLarge {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl fmt::Display for ImageTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a {}x{} image has too many pixels to render",
+            self.width, self.height
+        )
+    }
+}
+
+impl Error for ImageTooLarge {}
+
+// Rectangle of pixels [x0, x1) x [y0, y1) rendered by a single job.
+#[derive(Copy, Clone)]
+struct Tile {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         image_width: i32,
         aspect_ratio: f64,
@@ -67,9 +184,6 @@ impl Camera {
 
         let center = lookfrom;
 
-        // Camera
-        let focal_length = (lookfrom - lookat).length();
-
         let theta = degrees_to_radians(vfov as f64);
         let h = f64::tan(theta / 2.0);
 
@@ -91,13 +205,13 @@ impl Camera {
         let pixel_delta_u = viewport_u / (image_width as f64);
         let pixel_delta_v = viewport_v / (image_height as f64);
 
-        // Calculate the location of the upper left pixel.
-        let viewport_upper_left = center - (focal_length * w) - viewport_u / 2.0 - viewport_v / 2.0;
+        // Calculate the location of the upper left pixel, on the plane of perfect focus.
+        let viewport_upper_left = center - (focus_dist * w) - viewport_u / 2.0 - viewport_v / 2.0;
 
         let pixel00_loc = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
 
         // Calculate the camera defocus disk basis vectors.
-        let defocus_radius = focus_dist as f64 * f64::tan(degrees_to_radians(defocus_angle / 2.0));
+        let defocus_radius = focus_dist * f64::tan(degrees_to_radians(defocus_angle / 2.0));
         let defocus_disk_u = u * defocus_radius;
         let defocus_disk_v = v * defocus_radius;
 
@@ -109,8 +223,8 @@ impl Camera {
             pixel00_loc,
             pixel_delta_u,
             pixel_delta_v,
-            pixel_samples_scale: 1.0 / (samples_per_pixel as f64),
             max_depth,
+            roulette_depth: 5,
             vfov,
 
             lookfrom,
@@ -120,17 +234,48 @@ impl Camera {
             defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
+
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            tile_size: 32,
+            seed: 0,
+
+            background: None,
+
+            shutter_open: 0.0,
+            shutter_close: 1.0,
+
+            fog: None,
+
+            lights: None,
+            integrator: Arc::new(NaivePathTracer),
+
+            adaptive: None,
+
+            material_numbers: Arc::default(),
         }
     }
-    pub fn ray_color<T: Hittable>(&self, r: Ray, world: &T, depth: i32) -> Color {
-        if depth <= 0 {
-            return Color::new(0.0, 0.0, 0.0);
+
+    pub fn fog_transmittance(&self, r: Ray, t: f64) -> f64 {
+        // Fraction of the light at parameter t along the ray that gets through the fog.
+        match self.fog {
+            Some(fog) if fog.density > 0.0 => f64::exp(-fog.density * t * r.direction().length()),
+            _ => 1.0,
         }
+    }
 
-        if let Some(rec) = world.hit(r, Interval::new(0.001, f64::INFINITY)) {
-            if let Some((scattered, attenuation)) = rec.material.scatter(&r, &rec) {
-                return attenuation * self.ray_color(scattered, world, depth - 1);
-            }
+    pub fn through_fog(&self, r: Ray, t: f64, color: Color) -> Color {
+        // Blends the color seen at parameter t along the ray with the fog in front of it.
+        let Some(fog) = self.fog.filter(|fog| fog.density > 0.0) else {
+            return color;
+        };
+
+        let transmittance = self.fog_transmittance(r, t);
+        transmittance * color + (1.0 - transmittance) * fog.color
+    }
+
+    pub fn background_color(&self, r: Ray) -> Color {
+        if let Some(background) = self.background {
+            return background;
         }
 
         let unit_direction = r.direction().unit();
@@ -138,59 +283,347 @@ impl Camera {
         (1.0 - a) * Color::new(1.0, 1.0, 1.0) + a * Color::new(0.5, 0.7, 1.0)
     }
 
-    pub fn render<T: Hittable>(&self, world: &T) {
-        let bar = Arc::new(ProgressBar::new(
-            (self.image_width * self.image_height) as u64,
-        ));
+    // Panics if the image does not fit in memory, see `render_with_sample_counts`.
+    pub fn render<T: Hittable + 'static>(&self, world: Arc<T>) -> Image {
+        self.render_with_sample_counts(world)
+            .expect("image too large to render")
+            .0
+    }
+
+    // Also returns the number of samples taken for each pixel, in row-major order. Fails before
+    // rendering anything if the pixels cannot be counted or held in memory.
+    pub fn render_with_sample_counts<T: Hittable + 'static>(
+        &self,
+        world: Arc<T>,
+    ) -> Result<(Image, Vec<i32>), ImageTooLarge> {
+        let too_large = ImageTooLarge {
+            width: self.image_width,
+            height: self.image_height,
+        };
+        let mut image = Image::try_new(self.image_width as usize, self.image_height as usize)
+            .ok_or(too_large)?;
+        let mut sample_counts = Vec::new();
+        sample_counts
+            .try_reserve_exact(image.pixels().len())
+            .map_err(|_| too_large)?;
+        sample_counts.resize(image.pixels().len(), 0);
+
+        let tiles = self.tiles();
+        let bar = ProgressBar::new(tiles.len() as u64);
+
+        info!(
+            "Rendering {} tiles on {} threads",
+            tiles.len(),
+            self.threads
+        );
+
+        let pool = ThreadPool::new(self.threads.max(1));
+        let mut camera = self.clone();
+        camera.material_numbers = Arc::new(MaterialNumbers::new(world.as_ref()));
+        let camera = Arc::new(camera);
+        let (tx, rx) = mpsc::channel();
+
+        for tile in tiles {
+            let camera = Arc::clone(&camera);
+            let world = Arc::clone(&world);
+            let tx = tx.clone();
+            pool.execute(move || {
+                let pixels = camera.render_tile(tile, world.as_ref());
+                tx.send((tile, pixels))
+                    .expect("render result receiver dropped");
+            });
+        }
+        drop(tx);
+
+        // Assemble the tiles in the framebuffer as they complete.
+        for (tile, pixels) in rx {
+            let mut pixels = pixels.into_iter();
+            for j in tile.y0..tile.y1 {
+                for i in tile.x0..tile.x1 {
+                    let (color, samples) = pixels.next().unwrap();
+                    image.set(i as usize, j as usize, color);
+                    sample_counts[self.pixel_index(i, j)] = samples;
+                }
+            }
+            bar.inc(1);
+        }
+
+        bar.finish();
+        Ok((image, sample_counts))
+    }
+
+    fn pixel_index(&self, i: i32, j: i32) -> usize {
+        j as usize * self.image_width as usize + i as usize
+    }
+
+    // False-color image of per-pixel sample counts, from dark blue for a single sample through
+    // red to yellow for `samples_per_pixel`.
+    pub fn sample_heatmap(&self, sample_counts: &[i32]) -> Image {
+        let mut image = Image::new(self.image_width as usize, self.image_height as usize);
+        let span = f64::max(1.0, (self.samples_per_pixel - 1) as f64);
+        for (index, &samples) in sample_counts.iter().enumerate() {
+            let t = ((samples - 1) as f64 / span).clamp(0.0, 1.0);
+            let color = if t < 0.5 {
+                Color::new(2.0 * t, 0.0, 0.25 * (1.0 - 2.0 * t))
+            } else {
+                Color::new(1.0, 2.0 * t - 1.0, 0.0)
+            };
+            let (i, j) = (index % image.width(), index / image.width());
+            image.set(i, j, color);
+        }
+        image
+    }
+
+    fn tiles(&self) -> Vec<Tile> {
+        let tile_size = self.tile_size.max(1);
+        let mut tiles = Vec::new();
+        for y0 in (0..self.image_height).step_by(tile_size as usize) {
+            for x0 in (0..self.image_width).step_by(tile_size as usize) {
+                tiles.push(Tile {
+                    x0,
+                    y0,
+                    x1: i32::min(x0.saturating_add(tile_size), self.image_width),
+                    y1: i32::min(y0.saturating_add(tile_size), self.image_height),
+                });
+            }
+        }
+        tiles
+    }
 
-        print!("P3\n{} {}\n255\n", self.image_width, self.image_height);
+    fn render_tile(&self, tile: Tile, world: &dyn Hittable) -> Vec<(Color, i32)> {
+        // Returns the tile's pixel colors and sample counts in row-major order.
+        let mut pixels =
+            Vec::with_capacity((tile.x1 - tile.x0) as usize * (tile.y1 - tile.y0) as usize);
 
-        for j in 0..self.image_height {
-            for i in 0..self.image_width {
+        for j in tile.y0..tile.y1 {
+            for i in tile.x0..tile.x1 {
                 let mut pixel_color = Color::new(0.0, 0.0, 0.0);
 
-                for _sample in 0..self.samples_per_pixel {
-                    let r = self.get_ray(i, j);
-                    pixel_color += self.ray_color(r, world, self.max_depth);
+                // Each pixel draws from its own stream so the image does not depend on how
+                // pixels are distributed over threads.
+                let mut rng = seeded_rng(self.seed, self.pixel_index(i, j) as u64);
+
+                let mut stats = SampleStats::default();
+                let mut samples = 0;
+                while samples < self.samples_per_pixel {
+                    let r = self.get_ray(i, j, &mut rng);
+                    let sample = self.integrator.radiance(self, r, world, &mut rng);
+                    pixel_color += sample;
+                    samples += 1;
+
+                    if let Some(adaptive) = self.adaptive {
+                        stats.push(sample);
+                        if adaptive.converged(&stats) {
+                            break;
+                        }
+                    }
                 }
-                color::write_color(self.pixel_samples_scale * pixel_color);
-                bar.inc(1);
+                pixels.push((pixel_color / samples.max(1) as f64, samples));
             }
         }
-
-        bar.finish();
+        pixels
     }
 
-    fn get_ray(&self, i: i32, j: i32) -> Ray {
+    fn get_ray(&self, i: i32, j: i32, rng: &mut Rng) -> Ray {
         // Construct a camera ray originating from the defocus disk and directed at a randomly
-        // sampled point around the pixel location i, j.
+        // sampled point around the pixel location i, j, at a random time while the shutter is
+        // open.
 
-        let offset = self.sample_square();
+        let offset = self.sample_square(rng);
         let pixel_sample = self.pixel00_loc
             + ((i as f64 + offset.x()) * self.pixel_delta_u)
             + ((j as f64 + offset.y()) * self.pixel_delta_v);
 
-        let ray_origin;
-
-        if self.defocus_angle <= 0.0 {
-            ray_origin = self.center;
+        let ray_origin = if self.defocus_angle <= 0.0 {
+            self.center
         } else {
-            ray_origin = self.defocus_disk_sample();
-        }
+            self.defocus_disk_sample(rng)
+        };
 
         let ray_direction = pixel_sample - ray_origin;
+        let ray_time = random_f64_bounded(rng, self.shutter_open, self.shutter_close);
 
-        Ray::new(ray_origin, ray_direction)
+        Ray::with_time(ray_origin, ray_direction, ray_time)
     }
 
-    fn sample_square(&self) -> Vec3 {
+    fn sample_square(&self, rng: &mut Rng) -> Vec3 {
         // Returns the vector to a random point in the [-.5,-.5]-[+.5,+.5] unit square.
-        Vec3::new(random_f64() - 0.5, random_f64() - 0.5, 0.0)
+        Vec3::new(random_f64(rng) - 0.5, random_f64(rng) - 0.5, 0.0)
     }
 
-    fn defocus_disk_sample(&self) -> Point3 {
+    fn defocus_disk_sample(&self, rng: &mut Rng) -> Point3 {
         // Returns a random point in the camera defocus disk.
-        let p = Vec3::random_in_unit_disk();
-        return self.center + (p[0] * self.defocus_disk_u) + (p[1] * self.defocus_disk_v);
+        let p = Vec3::random_in_unit_disk(rng);
+        self.center + (p[0] * self.defocus_disk_u) + (p[1] * self.defocus_disk_v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::hittable_list::HittableList;
+    use crate::material::{DiffuseLight, Lambertian, Metal};
+    use crate::sphere::Sphere;
+
+    fn small_camera() -> Camera {
+        Camera::new(
+            24,
+            1.5,
+            4,
+            10,
+            40,
+            2.0,
+            3.0,
+            Point3::new(0.0, 0.5, 2.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    fn small_world() -> Arc<HittableList> {
+        let mut world = HittableList::new();
+        let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        world.add(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, ground));
+        let metal = Metal::new(Color::new(0.8, 0.6, 0.2), 0.3);
+        world.add(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, metal));
+        Arc::new(world)
+    }
+
+    #[test]
+    fn test_render_is_deterministic() {
+        let world = small_world();
+
+        let mut camera = small_camera();
+        camera.threads = 1;
+        camera.tile_size = 32;
+        let reference = camera.render(world.clone());
+
+        camera.threads = 3;
+        camera.tile_size = 5;
+        let image = camera.render(world.clone());
+        assert_eq!(
+            format!("{:?}", reference.pixels()),
+            format!("{:?}", image.pixels())
+        );
+
+        camera.seed = 1;
+        let image = camera.render(world);
+        assert_ne!(
+            format!("{:?}", reference.pixels()),
+            format!("{:?}", image.pixels())
+        );
+    }
+
+    #[test]
+    fn test_shutter() {
+        let mut camera = small_camera();
+        camera.shutter_open = 0.25;
+        camera.shutter_close = 0.5;
+        let mut rng = seeded_rng(0, 0);
+        for _ in 0..100 {
+            let time = camera.get_ray(3, 4, &mut rng).time();
+            assert!((0.25..0.5).contains(&time), "{}", time);
+        }
+    }
+
+    #[test]
+    fn test_image_too_large() {
+        let camera = Camera::new(
+            i32::MAX,
+            1e-9,
+            1,
+            1,
+            40,
+            0.0,
+            3.0,
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let err = camera
+            .render_with_sample_counts(Arc::new(HittableList::new()))
+            .unwrap_err();
+        assert_eq!((err.width, err.height), (i32::MAX, i32::MAX));
+    }
+
+    #[test]
+    fn test_background_and_emission() {
+        let mut camera = small_camera();
+        camera.background = Some(Color::new(0.0, 0.0, 0.0));
+        let image = camera.render(Arc::new(HittableList::new()));
+        assert!(image.pixels().iter().all(|c| c.length() == 0.0));
+
+        // From inside a light, every sample sees its emission and nothing else.
+        let mut world = HittableList::new();
+        let light = DiffuseLight::new(Color::new(4.0, 2.0, 1.0));
+        world.add(Sphere::new(Point3::new(0.0, 0.0, 0.0), 10.0, light));
+        let image = camera.render(Arc::new(world));
+        assert!(image.pixels().iter().all(|c| c.x() == 4.0 && c.z() == 1.0));
+    }
+
+    #[test]
+    fn test_adaptive_sampling() {
+        let world = small_world();
+        let mut camera = Camera::new(
+            24,
+            1.5,
+            256,
+            10,
+            40,
+            0.0,
+            3.0,
+            Point3::new(0.0, 0.5, 2.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let (reference, counts) = camera.render_with_sample_counts(world.clone()).unwrap();
+        assert!(counts.iter().all(|&samples| samples == 256));
+
+        // The sky converges at once, the fuzzy metal and the shadowed ground take longer, and
+        // the image stays close to the one with every sample.
+        camera.adaptive = Some(AdaptiveSampling::new(0.05));
+        let (image, counts) = camera.render_with_sample_counts(world).unwrap();
+        assert_eq!(counts[0], 16);
+        assert!(counts.iter().any(|&samples| samples > 64));
+        let total: i32 = counts.iter().sum();
+        assert!(total < 256 * counts.len() as i32 / 2, "{}", total);
+        let mean = |image: &Image| image.pixels().iter().map(|c| c.y()).sum::<f64>();
+        assert!(f64::abs(mean(&image) / mean(&reference) - 1.0) < 0.02);
+
+        let heatmap = camera.sample_heatmap(&counts);
+        assert_eq!(heatmap.width(), image.width());
+        let expected = Color::new(30.0 / 255.0, 0.0, 0.25 * (1.0 - 30.0 / 255.0));
+        assert!((heatmap.get(0, 0) - expected).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_fog() {
+        let mut camera = small_camera();
+        camera.background = Some(Color::new(0.0, 0.0, 0.0));
+        let fog_color = Color::new(0.5, 0.6, 0.7);
+        camera.fog = Some(Fog {
+            density: 0.5,
+            color: fog_color,
+        });
+
+        // Rays leaving the scene see only fog.
+        let image = camera.render(Arc::new(HittableList::new()));
+        assert!(image
+            .pixels()
+            .iter()
+            .all(|c| (*c - fog_color).length() < 1e-9));
+
+        // A light at distance 2 is seen through exp(-1) of the fog.
+        let mut world = HittableList::new();
+        let light = DiffuseLight::new(Color::new(1.0, 1.0, 1.0));
+        world.add(Sphere::new(Point3::new(0.0, 0.0, 0.0), 2.0, light));
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -2.0));
+        let mut rng = seeded_rng(0, 0);
+        let color = NaivePathTracer.radiance(&camera, r, &world, &mut rng);
+        let transmittance = f64::exp(-1.0);
+        let expected =
+            transmittance * Color::new(1.0, 1.0, 1.0) + (1.0 - transmittance) * fog_color;
+        assert!((color - expected).length() < 1e-9);
     }
 }
@@ -1,3 +1,5 @@
+use std::io::{self, Write};
+
 use crate::interval::Interval;
 use crate::vec3::Vec3;
 pub type Color = Vec3;
@@ -9,12 +11,35 @@ pub fn linear_to_gamma(linear_component: f64) -> f64 {
     0.0
 }
 
-pub fn write_color(color: Color) {
+pub fn gamma_to_linear(gamma_component: f64) -> f64 {
+    // Inverse of `linear_to_gamma`, for colors read from image files.
+    if gamma_component > 0.0 {
+        return gamma_component * gamma_component;
+    }
+    0.0
+}
+
+pub fn to_bytes(color: Color) -> [u8; 3] {
     // Translate the [0,1] component values to the byte range [0,255].
     let intensity = Interval::new(0.000, 0.999);
-    let ir = 255.999 * intensity.clamp(linear_to_gamma(color.x()));
-    let ig = 255.999 * intensity.clamp(linear_to_gamma(color.y()));
-    let ib = 255.999 * intensity.clamp(linear_to_gamma(color.z()));
+    let ir = 256.0 * intensity.clamp(linear_to_gamma(color.x()));
+    let ig = 256.0 * intensity.clamp(linear_to_gamma(color.y()));
+    let ib = 256.0 * intensity.clamp(linear_to_gamma(color.z()));
+
+    [ir as u8, ig as u8, ib as u8]
+}
+
+pub fn to_words(color: Color) -> [u16; 3] {
+    // Translate the [0,1] component values to the 16-bit range [0,65535].
+    let intensity = Interval::new(0.0, 1.0);
+    let ir = 65535.0 * intensity.clamp(linear_to_gamma(color.x()));
+    let ig = 65535.0 * intensity.clamp(linear_to_gamma(color.y()));
+    let ib = 65535.0 * intensity.clamp(linear_to_gamma(color.z()));
+
+    [ir.round() as u16, ig.round() as u16, ib.round() as u16]
+}
 
-    println!("{} {} {}", ir, ig, ib)
+pub fn write_color(out: &mut dyn Write, color: Color) -> io::Result<()> {
+    let [ir, ig, ib] = to_bytes(color);
+    writeln!(out, "{} {} {}", ir, ig, ib)
 }
@@ -0,0 +1,139 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::{self, Interval};
+use crate::material::{Isotropic, Material};
+use crate::ray::Ray;
+use crate::rtweekend::{hashed_f64, random_f64, seeded_rng};
+use crate::vec3::Vec3;
+
+// Volume of constant density filling a boundary object, e.g. smoke or mist. The boundary must
+// be closed and convex, its own material is ignored.
+pub struct ConstantMedium<H: Hittable> {
+    boundary: H,
+    neg_inv_density: f64,
+    phase_function: Isotropic,
+    stream: u64, // Keeps the samples of overlapping media independent, unless they are identical
+}
+
+impl<H: Hittable> ConstantMedium<H> {
+    // Panics if `density` is not positive.
+    pub fn new(boundary: H, density: f64, phase_function: Isotropic) -> Self {
+        assert!(
+            density.is_finite() && density > 0.0,
+            "medium density must be positive"
+        );
+        let bbox = boundary.bounding_box();
+        let stream = hashed_f64(&[
+            bbox.x.min, bbox.x.max, bbox.y.min, bbox.y.max, bbox.z.min, bbox.z.max, density,
+        ])
+        .to_bits();
+        ConstantMedium {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function,
+            stream,
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for ConstantMedium<H> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        // Find where the ray enters and leaves the boundary, wherever its origin is.
+        let rec1 = self.boundary.hit(r, interval::UNIVERSE)?;
+        let rec2 = self
+            .boundary
+            .hit(r, Interval::new(rec1.t + 0.0001, f64::INFINITY))?;
+
+        let t1 = f64::max(rec1.t, f64::max(ray_t.min, 0.0));
+        let t2 = f64::min(rec2.t, ray_t.max);
+        if t1 >= t2 {
+            return None;
+        }
+
+        // Sample the free-flight distance from the generator the integrator seeded the ray with.
+        let random = random_f64(&mut seeded_rng(r.seed(), self.stream));
+
+        let d = r.direction();
+        let ray_length = d.length();
+        let distance_inside_boundary = (t2 - t1) * ray_length;
+        let hit_distance = self.neg_inv_density * f64::ln(1.0 - random);
+
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t1 + hit_distance / ray_length;
+        Some(HitRecord {
+            t,
+            p: r.at(t),
+            normal: Vec3::new(1.0, 0.0, 0.0), // arbitrary
+            u: 0.0,
+            v: 0.0,
+            front_face: true, // also arbitrary
+            material: &self.phase_function,
+        })
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        visit(&self.phase_function)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::sphere::Sphere;
+    use crate::transform::Translate;
+    use crate::vec3::Point3;
+
+    fn fog(density: f64) -> ConstantMedium<Sphere<Isotropic>> {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let boundary = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Isotropic::new(white));
+        ConstantMedium::new(boundary, density, Isotropic::new(white))
+    }
+
+    #[test]
+    fn test_free_flight() {
+        // The fraction of rays crossing the unit sphere through its center without scattering
+        // is exp(-density * 2).
+        let passed = |medium: &dyn Hittable| {
+            let count = 10000;
+            let mut passed = 0;
+            for i in 0..count {
+                let r =
+                    Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)).with_seed(i);
+                match medium.hit(r, Interval::new(0.001, f64::INFINITY)) {
+                    Some(rec) => assert!((4.0..=6.0).contains(&rec.t), "{}", rec.t),
+                    None => passed += 1,
+                }
+            }
+            passed as f64 / count as f64
+        };
+        let expected = f64::exp(-1.0);
+        assert!(f64::abs(passed(&fog(0.5)) - expected) < 0.02);
+
+        // Transforms pass the seed on to the medium.
+        let moved = Translate::new(fog(0.5), Vec3::new(0.0, 0.0, 0.0));
+        assert!(f64::abs(passed(&moved) - expected) < 0.02);
+
+        // Rays starting inside the medium scatter ahead of their origin, rays leaving it
+        // never scatter.
+        let dense = fog(100.0);
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = dense.hit(r, Interval::new(0.001, f64::INFINITY)).unwrap();
+        assert!(rec.t > 0.001 && rec.t < 1.0);
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(dense.hit(r, Interval::new(0.001, f64::INFINITY)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "medium density must be positive")]
+    fn test_zero_density() {
+        fog(0.0);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::plane::PlaneBasis;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use core::f64;
+
+// Flat disk facing `normal`. Surface coordinates are polar: u is the angle around the center and
+// v the distance from it, both scaled to [0,1].
+pub struct Disk<M: Material> {
+    basis: PlaneBasis,
+    pub mat: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Disk<M> {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, mat: M) -> Self {
+        let unit = PlaneBasis::from_normal(center, normal);
+        let (u, v) = (radius * unit.u, radius * unit.v);
+
+        // The disk fits in the square with the same center and edges 2u and 2v.
+        let bbox_diagonal1 = Aabb::from_points(center - u - v, center + u + v);
+        let bbox_diagonal2 = Aabb::from_points(center + u - v, center - u + v);
+
+        Disk {
+            basis: PlaneBasis::new(center, u, v),
+            mat,
+            bbox: Aabb::surrounding(bbox_diagonal1, bbox_diagonal2),
+        }
+    }
+}
+
+impl<M: Material> Hittable for Disk<M> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        let (t, alpha, beta) = self.basis.hit(r, ray_t)?;
+
+        let distance = f64::sqrt(alpha * alpha + beta * beta);
+        if distance > 1.0 {
+            return None;
+        }
+
+        let mut rec = HitRecord {
+            t,
+            p: r.at(t),
+            normal: self.basis.normal,
+            u: (f64::atan2(beta, alpha) + f64::consts::PI) / (2.0 * f64::consts::PI),
+            v: distance,
+            front_face: false,
+            material: &self.mat,
+        };
+        rec.set_face_normal(r, self.basis.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        visit(&self.mat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::interval;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn test_disk() {
+        let disk = Disk::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            2.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let up = Ray::new(Point3::new(1.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = disk.hit(up, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 1.0);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal.y(), -1.0);
+        assert!(f64::abs(rec.v - f64::sqrt(2.0) / 2.0) < 1e-9);
+
+        // Inside the bounding square, outside the disk.
+        let corner = Ray::new(Point3::new(1.5, 0.0, 1.5), Vec3::new(0.0, 1.0, 0.0));
+        assert!(disk.hit(corner, interval::UNIVERSE).is_none());
+
+        let bbox = disk.bounding_box();
+        assert!(f64::abs(bbox.x.max - 2.0) < 1e-9);
+        assert!(bbox.y.size() < 0.001);
+    }
+}
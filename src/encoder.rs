@@ -0,0 +1,565 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use half::f16;
+
+use crate::color::{self, Color};
+use crate::image::Image;
+
+pub trait Encoder {
+    fn encode(&self, image: &Image, out: &mut dyn Write) -> io::Result<()>;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PpmFormat {
+    Ascii,  // P3
+    Binary, // P6
+}
+
+// PPM, gamma corrected and quantized to 8 bits per channel.
+pub struct PpmEncoder {
+    pub format: PpmFormat,
+}
+
+impl PpmEncoder {
+    pub fn new(format: PpmFormat) -> Self {
+        PpmEncoder { format }
+    }
+}
+
+impl Encoder for PpmEncoder {
+    fn encode(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        match self.format {
+            PpmFormat::Ascii => {
+                write!(out, "P3\n{} {}\n255\n", image.width(), image.height())?;
+                for pixel_color in image.pixels() {
+                    color::write_color(out, *pixel_color)?;
+                }
+            }
+            PpmFormat::Binary => {
+                write!(out, "P6\n{} {}\n255\n", image.width(), image.height())?;
+                let data: Vec<u8> = image
+                    .pixels()
+                    .iter()
+                    .flat_map(|c| color::to_bytes(*c))
+                    .collect();
+                out.write_all(&data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Portable Float Map, storing linear, unclamped colors as 32-bit floats.
+pub struct PfmEncoder;
+
+impl Encoder for PfmEncoder {
+    fn encode(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        // A negative scale marks little-endian data.
+        write!(out, "PF\n{} {}\n-1.0\n", image.width(), image.height())?;
+
+        // Scanlines are stored from the bottom of the image to the top.
+        let mut line = Vec::with_capacity(image.width() * 3 * 4);
+        for row in image.rows().rev() {
+            line.clear();
+            for pixel_color in row {
+                for channel in 0..3 {
+                    line.extend((pixel_color[channel] as f32).to_le_bytes());
+                }
+            }
+            out.write_all(&line)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PngBitDepth {
+    Eight,
+    Sixteen,
+}
+
+// PNG with the same gamma correction as the PPM output, at 8 or 16 bits per channel.
+pub struct PngEncoder {
+    pub bit_depth: PngBitDepth,
+}
+
+impl PngEncoder {
+    pub fn new(bit_depth: PngBitDepth) -> Self {
+        PngEncoder { bit_depth }
+    }
+}
+
+impl Encoder for PngEncoder {
+    fn encode(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, image.width() as u32, image.height() as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        // Pixels are encoded with `linear_to_gamma`, i.e. a gamma of 2.
+        encoder.set_source_gamma(png::ScaledFloat::new(1.0 / 2.0));
+
+        let data: Vec<u8> = match self.bit_depth {
+            PngBitDepth::Eight => {
+                encoder.set_depth(png::BitDepth::Eight);
+                image
+                    .pixels()
+                    .iter()
+                    .flat_map(|c| color::to_bytes(*c))
+                    .collect()
+            }
+            PngBitDepth::Sixteen => {
+                // PNG stores 16-bit samples in big-endian order.
+                encoder.set_depth(png::BitDepth::Sixteen);
+                image
+                    .pixels()
+                    .iter()
+                    .flat_map(|c| color::to_words(*c))
+                    .flat_map(u16::to_be_bytes)
+                    .collect()
+            }
+        };
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExrPixelType {
+    Half,
+    Float,
+}
+
+// Uncompressed scanline OpenEXR with linear, unclamped R, G and B channels.
+pub struct ExrEncoder {
+    pub pixel_type: ExrPixelType,
+}
+
+impl ExrEncoder {
+    pub fn new(pixel_type: ExrPixelType) -> Self {
+        ExrEncoder { pixel_type }
+    }
+
+    fn write_channel(&self, buf: &mut Vec<u8>, value: f64) {
+        match self.pixel_type {
+            ExrPixelType::Half => buf.extend(f16::from_f64(value).to_le_bytes()),
+            ExrPixelType::Float => buf.extend((value as f32).to_le_bytes()),
+        }
+    }
+}
+
+fn write_exr_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend(name.as_bytes());
+    header.push(0);
+    header.extend(kind.as_bytes());
+    header.push(0);
+    header.extend((value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+impl Encoder for ExrEncoder {
+    fn encode(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        let (pixel_type, channel_size) = match self.pixel_type {
+            ExrPixelType::Half => (1_i32, 2),
+            ExrPixelType::Float => (2_i32, 4),
+        };
+
+        // Magic number and version 2, single-part scanline file.
+        let mut header = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+
+        // Channels must be listed in alphabetical order.
+        let mut channels = Vec::new();
+        for name in ["B", "G", "R"] {
+            channels.extend(name.as_bytes());
+            channels.push(0);
+            channels.extend(pixel_type.to_le_bytes());
+            channels.extend([0, 0, 0, 0]); // pLinear and reserved bytes
+            channels.extend(1_i32.to_le_bytes()); // x sampling
+            channels.extend(1_i32.to_le_bytes()); // y sampling
+        }
+        channels.push(0);
+        write_exr_attribute(&mut header, "channels", "chlist", &channels);
+
+        write_exr_attribute(&mut header, "compression", "compression", &[0]);
+
+        let mut window = Vec::new();
+        for v in [0, 0, image.width() as i32 - 1, image.height() as i32 - 1] {
+            window.extend(v.to_le_bytes());
+        }
+        write_exr_attribute(&mut header, "dataWindow", "box2i", &window);
+        write_exr_attribute(&mut header, "displayWindow", "box2i", &window);
+
+        write_exr_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        write_exr_attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1.0_f32.to_le_bytes(),
+        );
+        write_exr_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        write_exr_attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1.0_f32.to_le_bytes(),
+        );
+        header.push(0);
+
+        out.write_all(&header)?;
+
+        // Offset table: one uncompressed scanline per block, each prefixed by its y coordinate
+        // and data size.
+        let line_size = image.width() * 3 * channel_size;
+        let table_size = image.height() * 8;
+        for y in 0..image.height() {
+            let offset = header.len() + table_size + y * (8 + line_size);
+            out.write_all(&(offset as u64).to_le_bytes())?;
+        }
+
+        let mut line = Vec::with_capacity(8 + line_size);
+        for (y, row) in image.rows().enumerate() {
+            line.clear();
+            line.extend((y as i32).to_le_bytes());
+            line.extend((line_size as i32).to_le_bytes());
+            for channel in [2, 1, 0] {
+                for pixel_color in row {
+                    self.write_channel(&mut line, pixel_color[channel]);
+                }
+            }
+            out.write_all(&line)?;
+        }
+        Ok(())
+    }
+}
+
+// Radiance RGBE (.hdr), storing linear, unclamped colors with a shared exponent.
+pub struct HdrEncoder;
+
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let r = f64::max(color.x(), 0.0);
+    let g = f64::max(color.y(), 0.0);
+    let b = f64::max(color.z(), 0.0);
+
+    let v = f64::max(r, f64::max(g, b));
+    if v < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    // Split v into a mantissa in [0.5, 1) and a power of two exponent.
+    let exponent = v.log2().floor() as i32 + 1;
+    let scale = 256.0 / f64::powi(2.0, exponent);
+
+    [
+        (r * scale) as u8,
+        (g * scale) as u8,
+        (b * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+impl Encoder for HdrEncoder {
+    fn encode(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        write!(
+            out,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            image.height(),
+            image.width()
+        )?;
+
+        // Scanlines are written flat, without run-length encoding.
+        let data: Vec<u8> = image.pixels().iter().flat_map(|c| to_rgbe(*c)).collect();
+        out.write_all(&data)
+    }
+}
+
+// Format specific settings used when picking an encoder from a file name.
+#[derive(Copy, Clone, Debug)]
+pub struct OutputOptions {
+    pub png_bit_depth: PngBitDepth,
+    pub exr_pixel_type: ExrPixelType,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            png_bit_depth: PngBitDepth::Eight,
+            exr_pixel_type: ExrPixelType::Half,
+        }
+    }
+}
+
+pub fn for_path(path: &Path, options: OutputOptions) -> io::Result<Box<dyn Encoder>> {
+    // Pick the encoder matching the extension of the output file.
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("ppm") => Ok(Box::new(PpmEncoder::new(PpmFormat::Binary))),
+        Some("pfm") => Ok(Box::new(PfmEncoder)),
+        Some("png") => Ok(Box::new(PngEncoder::new(options.png_bit_depth))),
+        Some("exr") => Ok(Box::new(ExrEncoder::new(options.exr_pixel_type))),
+        Some("hdr") => Ok(Box::new(HdrEncoder)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported output format: {}", path.display()),
+        )),
+    }
+}
+
+pub fn save(image: &Image, path: &Path, encoder: &dyn Encoder) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    encoder.encode(image, &mut out)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn test_ppm() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color::new(1.0, 0.25, 0.0));
+        image.set(1, 0, Color::new(2.0, -1.0, 0.5));
+
+        let mut out = Vec::new();
+        PpmEncoder::new(PpmFormat::Ascii)
+            .encode(&image, &mut out)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "P3\n2 1\n255\n255 128 0\n255 0 181\n"
+        );
+
+        let mut out = Vec::new();
+        PpmEncoder::new(PpmFormat::Binary)
+            .encode(&image, &mut out)
+            .unwrap();
+
+        assert_eq!(out, b"P6\n2 1\n255\n\xff\x80\x00\xff\x00\xb5");
+    }
+
+    #[test]
+    fn test_pfm() {
+        let mut image = Image::new(1, 2);
+        image.set(0, 0, Color::new(1.0, 2.0, 3.0));
+        image.set(0, 1, Color::new(-1.0, 0.0, 0.5));
+
+        let mut out = Vec::new();
+        PfmEncoder.encode(&image, &mut out).unwrap();
+
+        let header = b"PF\n1 2\n-1.0\n";
+        assert_eq!(&out[..header.len()], header);
+
+        let values: Vec<f32> = out[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(values, [-1.0, 0.0, 0.5, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_png() {
+        let mut image = Image::new(2, 2);
+        image.set(1, 0, Color::new(1.0, 1.0, 1.0));
+        image.set(0, 1, Color::new(0.25, 0.5, 2.0));
+        image.set(1, 1, Color::new(0.01, -1.0, 0.75));
+
+        for bit_depth in [PngBitDepth::Eight, PngBitDepth::Sixteen] {
+            let mut out = Vec::new();
+            PngEncoder::new(bit_depth).encode(&image, &mut out).unwrap();
+
+            let mut reader = png::Decoder::new(&out[..]).read_info().unwrap();
+            let mut data = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut data).unwrap();
+            assert_eq!((info.width, info.height), (2, 2));
+            assert_eq!(info.color_type, png::ColorType::Rgb);
+            assert_eq!(
+                reader.info().source_gamma,
+                Some(png::ScaledFloat::new(1.0 / 2.0))
+            );
+
+            let expected: Vec<u8> = match bit_depth {
+                PngBitDepth::Eight => {
+                    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+                    image
+                        .pixels()
+                        .iter()
+                        .flat_map(|c| color::to_bytes(*c))
+                        .collect()
+                }
+                PngBitDepth::Sixteen => {
+                    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+                    image
+                        .pixels()
+                        .iter()
+                        .flat_map(|c| color::to_words(*c))
+                        .flat_map(u16::to_be_bytes)
+                        .collect()
+                }
+            };
+            assert_eq!(&data[..info.buffer_size()], &expected[..]);
+        }
+
+        // Spot check the gamma-encoded values themselves.
+        let mut out = Vec::new();
+        PngEncoder::new(PngBitDepth::Eight)
+            .encode(&image, &mut out)
+            .unwrap();
+        let mut reader = png::Decoder::new(&out[..]).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!(&data[..6], &[0, 0, 0, 255, 255, 255]);
+        assert_eq!(&data[6..9], &[128, 181, 255]);
+    }
+
+    // Splits an OpenEXR header into (name, type, value) attributes, and returns them with the
+    // offset of the data following the header.
+    fn exr_attributes(data: &[u8]) -> (Vec<(String, String, Vec<u8>)>, usize) {
+        let read_string = |pos: &mut usize| {
+            let end = *pos + data[*pos..].iter().position(|&b| b == 0).unwrap();
+            let s = String::from_utf8(data[*pos..end].to_vec()).unwrap();
+            *pos = end + 1;
+            s
+        };
+
+        let mut attributes = Vec::new();
+        let mut pos = 8;
+        while data[pos] != 0 {
+            let name = read_string(&mut pos);
+            let kind = read_string(&mut pos);
+            let size = i32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+            attributes.push((name, kind, data[pos + 4..pos + 4 + size].to_vec()));
+            pos += 4 + size;
+        }
+        (attributes, pos + 1)
+    }
+
+    #[test]
+    fn test_exr() {
+        let mut image = Image::new(3, 2);
+        image.set(0, 0, Color::new(1.0, 0.5, 0.25));
+        image.set(2, 0, Color::new(3.0, -1.0, 100.0));
+        image.set(1, 1, Color::new(0.1, 0.2, 0.3));
+
+        for (pixel_type, channel_size) in [(ExrPixelType::Half, 2), (ExrPixelType::Float, 4)] {
+            let mut out = Vec::new();
+            ExrEncoder::new(pixel_type)
+                .encode(&image, &mut out)
+                .unwrap();
+            assert_eq!(&out[..8], &[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+
+            let (attributes, header_size) = exr_attributes(&out);
+            let attribute = |name: &str| {
+                let (_, kind, value) = attributes.iter().find(|a| a.0 == name).unwrap();
+                (kind.as_str(), value.as_slice())
+            };
+            let (kind, channels) = attribute("channels");
+            assert_eq!(kind, "chlist");
+            let exr_type = if channel_size == 2 { 1 } else { 2 };
+            for (k, name) in [b'B', b'G', b'R'].iter().enumerate() {
+                let channel = &channels[k * 18..(k + 1) * 18];
+                assert_eq!(&channel[..2], &[*name, 0]);
+                assert_eq!(
+                    i32::from_le_bytes(channel[2..6].try_into().unwrap()),
+                    exr_type
+                );
+            }
+            assert_eq!(attribute("compression"), ("compression", &[0][..]));
+            let window: Vec<i32> = attribute("dataWindow")
+                .1
+                .chunks(4)
+                .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            assert_eq!(window, [0, 0, 2, 1]);
+
+            // Decode every scanline through the offset table and compare with the image.
+            let line_size = 3 * 3 * channel_size;
+            for y in 0..2 {
+                let entry = &out[header_size + 8 * y..header_size + 8 * (y + 1)];
+                let offset = u64::from_le_bytes(entry.try_into().unwrap()) as usize;
+                let block_y = i32::from_le_bytes(out[offset..offset + 4].try_into().unwrap());
+                let size = i32::from_le_bytes(out[offset + 4..offset + 8].try_into().unwrap());
+                assert_eq!((block_y, size as usize), (y as i32, line_size));
+
+                let values: Vec<f64> = out[offset + 8..offset + 8 + line_size]
+                    .chunks(channel_size)
+                    .map(|b| match pixel_type {
+                        ExrPixelType::Half => f16::from_le_bytes([b[0], b[1]]).to_f64(),
+                        ExrPixelType::Float => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+                    })
+                    .collect();
+                for x in 0..3 {
+                    let expected = image.get(x, y);
+                    let decoded = Color::new(values[6 + x], values[3 + x], values[x]);
+                    let tolerance = if channel_size == 2 { 1e-3 } else { 1e-7 };
+                    assert!(
+                        (decoded - expected).length() <= tolerance * expected.length(),
+                        "{:?} {:?}",
+                        decoded,
+                        expected
+                    );
+                }
+            }
+            assert_eq!(out.len(), header_size + 2 * 8 + 2 * (8 + line_size));
+        }
+    }
+
+    #[test]
+    fn test_rgbe() {
+        assert_eq!(to_rgbe(Color::new(1.0, 1.0, 1.0)), [128, 128, 128, 129]);
+        assert_eq!(to_rgbe(Color::new(4.0, 2.0, 0.0)), [128, 64, 0, 131]);
+        assert_eq!(to_rgbe(Color::new(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::new(-1.0, 0.5, 0.0)), [0, 128, 0, 128]);
+
+        // Decoding gives back the color to within the precision of the shared exponent.
+        for color in [
+            Color::new(0.3, 0.6, 0.9),
+            Color::new(1000.0, 1.0, 0.001),
+            Color::new(1e-5, 2e-5, 3e-5),
+        ] {
+            let [r, g, b, e] = to_rgbe(color);
+            let scale = f64::powi(2.0, e as i32 - 136);
+            let decoded = Color::new(r as f64, g as f64, b as f64) * scale;
+            let largest = f64::max(color.x(), f64::max(color.y(), color.z()));
+            for k in 0..3 {
+                assert!(
+                    (decoded[k] - color[k]).abs() <= largest / 128.0,
+                    "{:?} {:?}",
+                    decoded,
+                    color
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hdr() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color::new(1.0, 1.0, 1.0));
+
+        let mut out = Vec::new();
+        HdrEncoder.encode(&image, &mut out).unwrap();
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(&out[header.len()..], &[128, 128, 128, 129, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_for_path() {
+        assert!(for_path(Path::new("out.ppm"), OutputOptions::default()).is_ok());
+        assert!(for_path(Path::new("out.pfm"), OutputOptions::default()).is_ok());
+        assert!(for_path(Path::new("OUT.PNG"), OutputOptions::default()).is_ok());
+        assert!(for_path(Path::new("out.exr"), OutputOptions::default()).is_ok());
+        assert!(for_path(Path::new("out.hdr"), OutputOptions::default()).is_ok());
+        assert!(for_path(Path::new("out.gif"), OutputOptions::default()).is_err());
+        assert!(for_path(Path::new("out"), OutputOptions::default()).is_err());
+    }
+}
@@ -1,7 +1,11 @@
+use std::sync::Arc;
+
 use crate::{
+    aabb::Aabb,
     interval::Interval,
     material::Material,
     ray::Ray,
+    rtweekend::Rng,
     vec3::{Point3, Vec3},
 };
 
@@ -10,6 +14,8 @@ pub struct HitRecord<'a> {
     pub p: Point3,
     pub normal: Vec3,
     pub t: f64,
+    pub u: f64, // Surface coordinates of the hit point
+    pub v: f64,
     pub front_face: bool,
 
     pub material: &'a dyn Material,
@@ -28,7 +34,46 @@ impl HitRecord<'_> {
     }
 }
 
-pub trait Hittable {
-    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord>;
+pub trait Hittable: Send + Sync {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>>;
+
+    fn bounding_box(&self) -> Aabb;
+
+    // Density, with respect to solid angle, of `random` picking `direction` from `origin` at
+    // `time`. Objects that cannot be sampled, i.e. everything but lights, return 0.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3, _time: f64) -> f64 {
+        0.0
+    }
+
+    // Returns a direction from `origin` towards a random point of the object at `time`.
+    fn random(&self, _origin: Point3, _time: f64, _rng: &mut Rng) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // Calls `visit` with the material of every surface the object is made of, always in the
+    // same order. Materials shared by several surfaces are visited once per surface.
+    fn materials(&self, _visit: &mut dyn FnMut(&dyn Material)) {}
 }
 
+// Lets a single object be shared between several lists or threads.
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        self.as_ref().hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.as_ref().bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        self.as_ref().pdf_value(origin, direction, time)
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        self.as_ref().random(origin, time, rng)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.as_ref().materials(visit)
+    }
+}
@@ -1,26 +1,47 @@
+use crate::aabb::{self, Aabb};
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
+use crate::material::Material;
+use crate::rtweekend::Rng;
+use crate::vec3::{Point3, Vec3};
+use rand::Rng as _;
 
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
+    bbox: Aabb,
 }
 
 impl HittableList {
     pub fn new() -> HittableList {
         HittableList {
             objects: Vec::new(),
+            bbox: aabb::EMPTY,
         }
     }
-    pub fn add<'a, T: Hittable + 'static>(&mut self, obj: T) {
+    pub fn add<T: Hittable + 'static>(&mut self, obj: T) {
+        self.bbox = Aabb::surrounding(self.bbox, obj.bounding_box());
         self.objects.push(Box::new(obj));
     }
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.bbox = aabb::EMPTY;
+    }
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable>> {
+        self.objects
+    }
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+impl Default for HittableList {
+    fn default() -> Self {
+        HittableList::new()
     }
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, r: crate::ray::Ray, ray_t: Interval) -> Option<HitRecord> {
+    fn hit(&self, r: crate::ray::Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
         // let mut hit_anything = false;
         let mut closest_so_far = ray_t.max;
         let mut ret = None;
@@ -34,4 +55,34 @@ impl Hittable for HittableList {
 
         ret
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        for object in &self.objects {
+            object.materials(visit);
+        }
+    }
+
+    // Picks one of the objects uniformly, so the density is the average of theirs.
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|obj| weight * obj.pdf_value(origin, direction, time))
+            .sum()
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let index = rng.gen_range(0..self.objects.len());
+        self.objects[index].random(origin, time, rng)
+    }
 }
@@ -0,0 +1,81 @@
+use crate::color::Color;
+
+// Framebuffer of linear, unclamped pixel colors stored in row-major order from the top-left
+// corner of the image.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![Color::new(0.0, 0.0, 0.0); width * height],
+        }
+    }
+
+    // Like `new`, but returns None instead of aborting when the pixels do not fit in memory.
+    pub fn try_new(width: usize, height: usize) -> Option<Self> {
+        let count = width.checked_mul(height)?;
+        let mut pixels = Vec::new();
+        pixels.try_reserve_exact(count).ok()?;
+        pixels.resize(count, Color::new(0.0, 0.0, 0.0));
+        Some(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Color]> {
+        self.pixels.chunks(self.width.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let image = Image::new(4, 3);
+        assert_eq!(image.width(), 4);
+        assert_eq!(image.height(), 3);
+        assert_eq!(image.pixels().len(), 12);
+        assert_eq!(image.get(3, 2).x(), 0.0);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut image = Image::new(4, 3);
+        image.set(1, 2, Color::new(1.0, 2.0, 3.0));
+
+        assert_eq!(image.get(1, 2).y(), 2.0);
+        assert_eq!(image.pixels()[9].z(), 3.0);
+        assert_eq!(image.rows().nth(2).unwrap()[1].x(), 1.0);
+    }
+}
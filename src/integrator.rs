@@ -0,0 +1,630 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rand::Rng as _;
+use serde::Deserialize;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::{Material, ScatterDirection};
+use crate::pdf::{CosinePdf, HittablePdf, Pdf};
+use crate::ray::Ray;
+use crate::rtweekend::{hashed_f64, random_f64, Rng};
+
+// Estimates the light arriving along camera rays. The camera averages the estimates of several
+// rays per pixel, and provides the rendering options: depth, background, fog and lights.
+pub trait Integrator: Send + Sync {
+    fn radiance(&self, camera: &Camera, r: Ray, world: &dyn Hittable, rng: &mut Rng) -> Color;
+}
+
+// Integrators selectable by name, e.g. from the command line or a scene file.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum IntegratorKind {
+    #[default]
+    Naive,
+    Mis,
+    AmbientOcclusion,
+    Direct,
+    Normals,
+    Depth,
+    Uv,
+    MaterialId,
+}
+
+pub const INTEGRATORS: [&str; 8] = [
+    "naive",
+    "mis",
+    "ao",
+    "direct",
+    "normals",
+    "depth",
+    "uv",
+    "material-id",
+];
+
+impl IntegratorKind {
+    pub fn build(self) -> Arc<dyn Integrator> {
+        match self {
+            IntegratorKind::Naive => Arc::new(NaivePathTracer),
+            IntegratorKind::Mis => Arc::new(MisPathTracer),
+            IntegratorKind::AmbientOcclusion => Arc::new(AmbientOcclusion::default()),
+            IntegratorKind::Direct => Arc::new(DirectLighting),
+            IntegratorKind::Normals => Arc::new(DebugView::Normals),
+            IntegratorKind::Depth => Arc::new(DebugView::Depth),
+            IntegratorKind::Uv => Arc::new(DebugView::Uv),
+            IntegratorKind::MaterialId => Arc::new(DebugView::MaterialId),
+        }
+    }
+}
+
+impl FromStr for IntegratorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "naive" => Ok(IntegratorKind::Naive),
+            "mis" => Ok(IntegratorKind::Mis),
+            "ao" => Ok(IntegratorKind::AmbientOcclusion),
+            "direct" => Ok(IntegratorKind::Direct),
+            "normals" => Ok(IntegratorKind::Normals),
+            "depth" => Ok(IntegratorKind::Depth),
+            "uv" => Ok(IntegratorKind::Uv),
+            "material-id" => Ok(IntegratorKind::MaterialId),
+            _ => Err(format!(
+                "unknown integrator '{}', expected one of: {}",
+                s,
+                INTEGRATORS.join(", ")
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for IntegratorKind {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for IntegratorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", INTEGRATORS[*self as usize])
+    }
+}
+
+// Light gathered along a path so far, and the fraction of light found further along it that
+// still reaches the camera.
+struct Path {
+    radiance: Color,
+    throughput: Color,
+}
+
+impl Path {
+    fn new() -> Self {
+        Path {
+            radiance: Color::new(0.0, 0.0, 0.0),
+            throughput: Color::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    fn add(&mut self, light: Color) {
+        self.radiance += self.throughput * light;
+    }
+
+    fn through_fog(&mut self, camera: &Camera, r: Ray, t: f64) {
+        // Adds the fog in front of parameter t along the ray, which hides what lies behind.
+        self.add(camera.through_fog(r, t, Color::new(0.0, 0.0, 0.0)));
+        self.throughput = camera.fog_transmittance(r, t) * self.throughput;
+    }
+
+    fn survives_roulette(&mut self, camera: &Camera, bounces: i32, rng: &mut Rng) -> bool {
+        // Russian roulette: past `camera.roulette_depth` bounces, dim paths are likely to end,
+        // and the survivors carry the light of those that did, which keeps the estimate
+        // unbiased.
+        if bounces < camera.roulette_depth {
+            return true;
+        }
+        let survival = f64::min(
+            1.0,
+            f64::max(
+                self.throughput.x(),
+                f64::max(self.throughput.y(), self.throughput.z()),
+            ),
+        );
+        if random_f64(rng) >= survival {
+            return false;
+        }
+        self.throughput = self.throughput / survival;
+        true
+    }
+}
+
+fn trace<'a>(
+    world: &'a dyn Hittable,
+    r: Ray,
+    ray_t: Interval,
+    rng: &mut Rng,
+) -> Option<HitRecord<'a>> {
+    // Hits the world with a ray seeded from the path's generator, for media along the ray.
+    world.hit(r.with_seed(rng.gen()), ray_t)
+}
+
+// Path tracing with one scattered ray per bounce, drawn from the material alone. Lights are only
+// found by bouncing into them, which makes it the reference the other integrators must match.
+pub struct NaivePathTracer;
+
+impl Integrator for NaivePathTracer {
+    fn radiance(&self, camera: &Camera, r: Ray, world: &dyn Hittable, rng: &mut Rng) -> Color {
+        let mut path = Path::new();
+        let mut r = r;
+
+        for depth in 0..camera.max_depth {
+            let Some(rec) = trace(world, r, Interval::new(0.001, f64::INFINITY), rng) else {
+                path.through_fog(camera, r, f64::INFINITY);
+                path.add(camera.background_color(r));
+                break;
+            };
+
+            path.through_fog(camera, r, rec.t);
+            path.add(rec.material.emitted(rec.u, rec.v, rec.p));
+
+            let Some(srec) = rec.material.scatter(&r, &rec, rng) else {
+                break;
+            };
+
+            let (scattered, weight) = match srec.direction {
+                ScatterDirection::Specular(scattered) => (scattered, srec.attenuation),
+                ScatterDirection::Pdf(pdf) => {
+                    let direction = pdf.generate(rng);
+                    let pdf_value = pdf.value(direction);
+                    let scattered = Ray::with_time(rec.p, direction, r.time());
+                    let scattering_pdf = rec.material.scatter_pdf(&r, &rec, &scattered);
+                    if scattering_pdf <= 0.0 || pdf_value <= 0.0 {
+                        break;
+                    }
+                    (scattered, srec.attenuation * scattering_pdf / pdf_value)
+                }
+            };
+
+            path.throughput *= weight;
+            if !path.survives_roulette(camera, depth + 1, rng) {
+                break;
+            }
+            r = scattered;
+        }
+
+        path.radiance
+    }
+}
+
+// Path tracing with next-event estimation: a shadow ray towards the lights and a scattered ray
+// per bounce, both weighted with the power heuristic (multiple importance sampling).
+pub struct MisPathTracer;
+
+impl Integrator for MisPathTracer {
+    fn radiance(&self, camera: &Camera, r: Ray, world: &dyn Hittable, rng: &mut Rng) -> Color {
+        path_trace_mis(camera, r, world, false, rng)
+    }
+}
+
+// Light reaching surfaces straight from emitters, as seen directly or through mirrors and
+// glass: the first bounce of `MisPathTracer` without the paths beyond it.
+pub struct DirectLighting;
+
+impl Integrator for DirectLighting {
+    fn radiance(&self, camera: &Camera, r: Ray, world: &dyn Hittable, rng: &mut Rng) -> Color {
+        path_trace_mis(camera, r, world, true, rng)
+    }
+}
+
+fn path_trace_mis(
+    camera: &Camera,
+    r: Ray,
+    world: &dyn Hittable,
+    direct_only: bool,
+    rng: &mut Rng,
+) -> Color {
+    let mut path = Path::new();
+    let mut r = r;
+    // Density with which the previous bounce picked `r`, None for camera rays and specular
+    // bounces, which light samples cannot reproduce.
+    let mut scatter_pdf = None;
+
+    for depth in 0..camera.max_depth {
+        let hit = trace(world, r, Interval::new(0.001, f64::INFINITY), rng);
+
+        // Light reached by a scattered ray was also reachable by a light sample from the
+        // previous bounce, which accounts for its share.
+        let emission_weight = match (scatter_pdf, &camera.lights) {
+            (Some(scatter_pdf), Some(lights)) => power_heuristic(
+                scatter_pdf,
+                lights.pdf_value(r.origin(), r.direction(), r.time()),
+            ),
+            _ => 1.0,
+        };
+
+        let Some(rec) = hit else {
+            path.through_fog(camera, r, f64::INFINITY);
+            path.add(emission_weight * camera.background_color(r));
+            break;
+        };
+
+        path.through_fog(camera, r, rec.t);
+        path.add(emission_weight * rec.material.emitted(rec.u, rec.v, rec.p));
+        if direct_only && scatter_pdf.is_some() {
+            break;
+        }
+
+        let Some(srec) = rec.material.scatter(&r, &rec, rng) else {
+            break;
+        };
+
+        let pdf = match srec.direction {
+            ScatterDirection::Specular(scattered) => {
+                path.throughput *= srec.attenuation;
+                if !path.survives_roulette(camera, depth + 1, rng) {
+                    break;
+                }
+                r = scattered;
+                scatter_pdf = None;
+                continue;
+            }
+            ScatterDirection::Pdf(pdf) => pdf,
+        };
+
+        // Light sample: the light seen in a direction towards the lights, if nothing blocks it.
+        if let Some(lights) = &camera.lights {
+            let light_pdf = HittablePdf::new(lights.as_ref(), rec.p, r.time());
+            let direction = light_pdf.generate(rng);
+            let light_pdf_value = light_pdf.value(direction);
+            let shadow_ray = Ray::with_time(rec.p, direction, r.time());
+            let scattering_pdf = rec.material.scatter_pdf(&r, &rec, &shadow_ray);
+
+            if light_pdf_value > 0.0 && scattering_pdf > 0.0 {
+                if let Some(light_rec) =
+                    trace(world, shadow_ray, Interval::new(0.001, f64::INFINITY), rng)
+                {
+                    let emitted = light_rec
+                        .material
+                        .emitted(light_rec.u, light_rec.v, light_rec.p);
+                    let weight = power_heuristic(light_pdf_value, pdf.value(direction));
+                    path.add(
+                        weight
+                            * camera.fog_transmittance(shadow_ray, light_rec.t)
+                            * (srec.attenuation * scattering_pdf * emitted)
+                            / light_pdf_value,
+                    );
+                }
+            }
+        }
+
+        // Scattered ray, importance sampled from the material alone.
+        let direction = pdf.generate(rng);
+        let pdf_value = pdf.value(direction);
+        let scattered = Ray::with_time(rec.p, direction, r.time());
+        let scattering_pdf = rec.material.scatter_pdf(&r, &rec, &scattered);
+        if scattering_pdf <= 0.0 || pdf_value <= 0.0 {
+            break;
+        }
+
+        path.throughput *= srec.attenuation * scattering_pdf / pdf_value;
+        if !path.survives_roulette(camera, depth + 1, rng) {
+            break;
+        }
+        r = scattered;
+        scatter_pdf = Some(pdf_value);
+    }
+
+    path.radiance
+}
+
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    // Weight of a sample drawn with density `pdf` when `other_pdf` could also have drawn it.
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b == 0.0 {
+        return 0.0;
+    }
+    a / (a + b)
+}
+
+// Fraction of the hemisphere above the first hit that is open within `distance`, cosine
+// weighted: white where nothing is nearby, darker in creases and corners. Materials and lights
+// are ignored.
+pub struct AmbientOcclusion {
+    pub distance: f64,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        AmbientOcclusion {
+            distance: f64::INFINITY,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(&self, _camera: &Camera, r: Ray, world: &dyn Hittable, rng: &mut Rng) -> Color {
+        let Some(rec) = trace(world, r, Interval::new(0.001, f64::INFINITY), rng) else {
+            return Color::new(1.0, 1.0, 1.0);
+        };
+
+        let direction = CosinePdf::new(rec.normal).generate(rng);
+        let occlusion_ray = Ray::with_time(rec.p, direction, r.time());
+        let occlusion_t = Interval::new(0.001, self.distance / direction.length());
+        match trace(world, occlusion_ray, occlusion_t, rng) {
+            Some(_) => Color::new(0.0, 0.0, 0.0),
+            None => Color::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+// False-color views of the first hit, for checking geometry and scene setup. Misses are black.
+pub enum DebugView {
+    // Unit normal mapped from [-1, 1] to [0, 1] per axis.
+    Normals,
+    // Distance from the ray origin, 0.5 at the distance between lookfrom and lookat and
+    // fading to black further away.
+    Depth,
+    // Surface coordinates u and v in red and green.
+    Uv,
+    // A color derived from the material, so objects sharing one look the same. Materials are told
+    // apart by their number in `Camera::material_numbers`, so colors stay the same between runs.
+    MaterialId,
+}
+
+impl Integrator for DebugView {
+    fn radiance(&self, camera: &Camera, r: Ray, world: &dyn Hittable, rng: &mut Rng) -> Color {
+        let Some(rec) = trace(world, r, Interval::new(0.001, f64::INFINITY), rng) else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+
+        match self {
+            DebugView::Normals => 0.5 * (rec.normal + Color::new(1.0, 1.0, 1.0)),
+            DebugView::Depth => {
+                let reference = (camera.lookat - camera.lookfrom).length();
+                let distance = rec.t * r.direction().length();
+                let shade = reference / (reference + distance);
+                Color::new(shade, shade, shade)
+            }
+            DebugView::Uv => Color::new(rec.u, rec.v, 0.0),
+            DebugView::MaterialId => material_color(camera, rec.material),
+        }
+    }
+}
+
+fn material_color(camera: &Camera, material: &dyn Material) -> Color {
+    // Hashes the material's number into a color, one channel at a time. Materials missing from
+    // the numbers, e.g. outside of `Camera::render`, fall back to their id.
+    let id = camera
+        .material_numbers
+        .get(material)
+        .unwrap_or_else(|| material.id()) as f64;
+    let channel = |k: f64| hashed_f64(&[id, k]);
+    Color::new(channel(0.0), channel(1.0), channel(2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable_list::HittableList;
+    use crate::material::{DiffuseLight, Lambertian, MaterialNumbers, Metal};
+    use crate::rtweekend::seeded_rng;
+    use crate::sphere::Sphere;
+    use crate::vec3::{Point3, Vec3};
+
+    fn small_camera(samples_per_pixel: i32) -> Camera {
+        Camera::new(
+            24,
+            1.5,
+            samples_per_pixel,
+            10,
+            40,
+            2.0,
+            3.0,
+            Point3::new(0.0, 0.5, 2.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_names() {
+        for name in INTEGRATORS {
+            let kind: IntegratorKind = name.parse().unwrap();
+            assert_eq!(kind.to_string(), name);
+        }
+        assert_eq!(
+            "ao".parse::<IntegratorKind>(),
+            Ok(IntegratorKind::AmbientOcclusion)
+        );
+        assert!("whitted".parse::<IntegratorKind>().is_err());
+    }
+
+    #[test]
+    fn test_light_sampling() {
+        // Sampling the light directly converges to the same image as waiting for bounces to
+        // find it.
+        let mut world = HittableList::new();
+        let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        world.add(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, ground));
+        let metal = Metal::new(Color::new(0.8, 0.8, 0.8), 0.1);
+        world.add(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, metal));
+        let light = Arc::new(Sphere::new(
+            Point3::new(0.0, 2.0, -1.0),
+            1.0,
+            DiffuseLight::new(Color::new(4.0, 4.0, 4.0)),
+        ));
+        world.add(light.clone());
+        let world = Arc::new(world);
+
+        let mut camera = small_camera(128);
+        camera.background = Some(Color::new(0.0, 0.0, 0.0));
+        let mean = |camera: &Camera| {
+            let image = camera.render(world.clone());
+            let sum: f64 = image.pixels().iter().map(|c| c.x()).sum();
+            sum / image.pixels().len() as f64
+        };
+
+        let naive = mean(&camera);
+        camera.lights = Some(light);
+        // The naive path tracer ignores the lights.
+        assert_eq!(mean(&camera), naive);
+        camera.integrator = Arc::new(MisPathTracer);
+        let mis = mean(&camera);
+        assert!(f64::abs(mis / naive - 1.0) < 0.05, "{} {}", naive, mis);
+
+        // Direct lighting leaves out the light bouncing off the ground onto the sphere.
+        camera.integrator = Arc::new(DirectLighting);
+        let direct = mean(&camera);
+        assert!(direct < mis, "{} {}", direct, mis);
+    }
+
+    #[test]
+    fn test_russian_roulette() {
+        // Ending paths at random converges to the same image as following them to the end.
+        let mut world = HittableList::new();
+        let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        world.add(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, ground));
+        let metal = Metal::new(Color::new(0.8, 0.6, 0.2), 0.3);
+        world.add(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, metal));
+        let world = Arc::new(world);
+
+        let mut camera = small_camera(128);
+        let mean = |camera: &Camera| {
+            let image = camera.render(world.clone());
+            let sum: f64 = image.pixels().iter().map(|c| c.x()).sum();
+            sum / image.pixels().len() as f64
+        };
+
+        camera.roulette_depth = camera.max_depth;
+        let full = mean(&camera);
+        camera.roulette_depth = 1;
+        let roulette = mean(&camera);
+        assert!(
+            f64::abs(roulette / full - 1.0) < 0.02,
+            "{} {}",
+            full,
+            roulette
+        );
+    }
+
+    #[test]
+    fn test_deep_paths() {
+        // Inside a perfect mirror, paths bounce until the depth limit without using up the stack.
+        let mut world = HittableList::new();
+        let mirror = Metal::new(Color::new(1.0, 1.0, 1.0), 0.0);
+        world.add(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, mirror));
+        let mut camera = small_camera(1);
+        camera.max_depth = 100_000;
+        camera.roulette_depth = camera.max_depth;
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.3, 0.2, -1.0));
+        let mut rng = seeded_rng(0, 0);
+        for integrator in [&NaivePathTracer as &dyn Integrator, &MisPathTracer] {
+            let color = integrator.radiance(&camera, r, &world, &mut rng);
+            assert_eq!(color.length(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_ambient_occlusion() {
+        let camera = small_camera(4);
+        let mut world = HittableList::new();
+        let ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        world.add(Sphere::new(Point3::new(0.0, -100.0, 0.0), 100.0, ground));
+        let mut rng = seeded_rng(0, 0);
+        let down = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        // An open plane is never occluded.
+        let ao = AmbientOcclusion::default();
+        assert_eq!(ao.radiance(&camera, down, &world, &mut rng).x(), 1.0);
+
+        // Under a low ceiling, most of the hemisphere is blocked, unless it is out of reach.
+        let ceiling = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        world.add(Sphere::new(Point3::new(0.0, 100.5, 0.0), 100.0, ceiling));
+        let down = Ray::new(Point3::new(0.0, 0.25, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let mut mean = |ao: &AmbientOcclusion| {
+            (0..1000)
+                .map(|_| ao.radiance(&camera, down, &world, &mut rng).x())
+                .sum::<f64>()
+                / 1000.0
+        };
+        assert!(mean(&ao) < 0.1);
+        assert_eq!(mean(&AmbientOcclusion { distance: 0.1 }), 1.0);
+    }
+
+    #[test]
+    fn test_debug_views() {
+        let camera = small_camera(4);
+        let mut world = HittableList::new();
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Sphere::new(
+            Point3::new(0.0, 0.0, -1.0),
+            0.5,
+            material.clone(),
+        ));
+        world.add(Sphere::new(Point3::new(0.0, 0.0, 1.0), 0.5, material));
+        let other = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        world.add(Sphere::new(Point3::new(1.0, 0.0, 0.0), 0.5, other));
+        let mut rng = seeded_rng(0, 0);
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        let mut view = |view: DebugView, direction: Vec3| {
+            view.radiance(&camera, Ray::new(origin, direction), &world, &mut rng)
+        };
+
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(view(DebugView::Normals, forward).z(), 1.0);
+        let depth = view(DebugView::Depth, forward).x();
+        let reference = (camera.lookat - camera.lookfrom).length();
+        assert!((depth - reference / (reference + 0.5)).abs() < 1e-9);
+        let uv = view(DebugView::Uv, forward);
+        assert!(uv.x() > 0.0 && uv.y() > 0.0 && uv.z() == 0.0);
+
+        let shared = view(DebugView::MaterialId, forward);
+        let behind = view(DebugView::MaterialId, Vec3::new(0.0, 0.0, 1.0));
+        let beside = view(DebugView::MaterialId, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!((shared - behind).length(), 0.0);
+        assert!((shared - beside).length() > 0.0);
+
+        let miss = view(DebugView::Normals, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(miss.length(), 0.0);
+    }
+
+    #[test]
+    fn test_material_numbers() {
+        // A scene built again has its materials elsewhere in memory, but renders the same.
+        let scene = || {
+            let mut world = HittableList::new();
+            let shared = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+            world.add(Sphere::new(
+                Point3::new(-0.6, 0.0, -1.0),
+                0.5,
+                shared.clone(),
+            ));
+            let metal = Metal::new(Color::new(0.8, 0.8, 0.8), 0.0);
+            world.add(Sphere::new(Point3::new(0.6, 0.0, -1.0), 0.5, metal));
+            world.add(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, shared));
+            Arc::new(world)
+        };
+        let (first, second) = (scene(), scene());
+
+        let numbers = MaterialNumbers::new(first.as_ref());
+        let mut found = Vec::new();
+        first.materials(&mut |material| found.push(numbers.get(material)));
+        assert_eq!(found, [Some(0), Some(1), Some(0)]);
+        let mut found = Vec::new();
+        second.materials(&mut |material| found.push(numbers.get(material)));
+        assert_eq!(found, [None, None, None]);
+
+        let mut camera = small_camera(1);
+        camera.integrator = Arc::new(DebugView::MaterialId);
+        assert_eq!(
+            format!("{:?}", camera.render(first).pixels()),
+            format!("{:?}", camera.render(second).pixels())
+        );
+    }
+}
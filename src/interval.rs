@@ -1,3 +1,4 @@
+#[derive(Copy, Clone, Debug)]
 pub struct Interval {
     pub min: f64,
     pub max: f64,
@@ -8,6 +9,14 @@ impl Interval {
         Interval { min, max }
     }
 
+    pub fn enclosing(a: Interval, b: Interval) -> Self {
+        // Create the interval tightly enclosing the two input intervals.
+        Interval {
+            min: f64::min(a.min, b.min),
+            max: f64::max(a.max, b.max),
+        }
+    }
+
     pub fn size(&self) -> f64 {
         self.max - self.min
     }
@@ -29,12 +38,18 @@ impl Interval {
         };
         x
     }
+
+    pub fn expand(&self, delta: f64) -> Interval {
+        let padding = delta / 2.0;
+        Interval::new(self.min - padding, self.max + padding)
+    }
 }
 
-const EMPTY: Interval = Interval::new(f64::INFINITY, -f64::INFINITY);
-const UNIVERSE: Interval = Interval::new(-f64::INFINITY, f64::INFINITY);
+pub const EMPTY: Interval = Interval::new(f64::INFINITY, -f64::INFINITY);
+pub const UNIVERSE: Interval = Interval::new(-f64::INFINITY, f64::INFINITY);
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
@@ -76,4 +91,23 @@ mod tests {
         assert_eq!(interval.clamp(1.0), 10.0);
         assert_eq!(interval.clamp(21.0), 20.0);
     }
+
+    #[test]
+    fn test_enclosing() {
+        let interval = Interval::enclosing(Interval::new(1.0, 2.0), Interval::new(5.0, 8.0));
+        assert_eq!(interval.min, 1.0);
+        assert_eq!(interval.max, 8.0);
+
+        let interval = Interval::enclosing(EMPTY, Interval::new(5.0, 8.0));
+        assert_eq!(interval.min, 5.0);
+        assert_eq!(interval.max, 8.0);
+    }
+
+    #[test]
+    fn test_expand() {
+        let interval = Interval::new(10.0, 20.0).expand(2.0);
+        assert_eq!(interval.min, 9.0);
+        assert_eq!(interval.max, 21.0);
+        assert_eq!(interval.size(), 12.0);
+    }
 }
@@ -0,0 +1,36 @@
+//! Ray tracer following the "Ray Tracing in One Weekend" book series.
+//!
+//! Build a [`hittable_list::HittableList`] of objects (optionally wrapped in a
+//! [`bvh::BvhNode`]), render it with a [`camera::Camera`] and write the returned
+//! [`image::Image`] with one of the [`encoder`]s. Scenes can also be loaded from TOML files
+//! with [`scene_file::load`].
+
+pub mod aabb;
+pub mod bvh;
+pub mod camera;
+pub mod color;
+pub mod constant_medium;
+pub mod disk;
+pub mod encoder;
+pub mod hittable;
+pub mod hittable_list;
+pub mod image;
+pub mod integrator;
+pub mod interval;
+pub mod material;
+pub mod matrix;
+pub mod mesh;
+pub mod onb;
+pub mod pdf;
+pub mod perlin;
+pub mod plane;
+pub mod quad;
+pub mod ray;
+pub mod rtweekend;
+pub mod scene;
+pub mod scene_file;
+pub mod sphere;
+pub mod texture;
+pub mod transform;
+pub mod triangle;
+pub mod vec3;
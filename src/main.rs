@@ -1,96 +1,268 @@
-use camera::Camera;
-use color::Color;
-use hittable_list::HittableList;
+use clap::Parser;
 use log::info;
-use material::{Dielectric, Lambertian, Metal};
-use rtweekend::{random_f64, random_f64_bounded};
-use sphere::Sphere;
-use vec3::{Point3, Vec3};
-
-mod camera;
-mod color;
-mod hittable;
-mod hittable_list;
-mod interval;
-mod material;
-mod ray;
-mod rtweekend;
-mod sphere;
-mod vec3;
-
-fn main() {
+use rtiow::bvh::BvhNode;
+use rtiow::camera::AdaptiveSampling;
+use rtiow::encoder::{
+    self, Encoder, ExrPixelType, OutputOptions, PngBitDepth, PpmEncoder, PpmFormat,
+};
+use rtiow::integrator::IntegratorKind;
+use rtiow::scene;
+use rtiow::scene_file;
+use rtiow::vec3::Vec3;
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Render a scene and write it as PPM, PNG, EXR, HDR or PFM.
+///
+/// Camera options override the defaults of the selected scene.
+#[derive(Parser, Debug)]
+#[command(name = "rtiow", version)]
+struct Args {
+    /// Built-in scene to render, or path to a TOML scene file
+    #[arg(long, default_value = "book1-final")]
+    scene: String,
+
+    /// Output file, its extension selects the format [default: P3 PPM on stdout]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Image width in pixels
+    #[arg(long, value_parser = checked(scene::check_at_least_one))]
+    width: Option<i32>,
+
+    /// Image width over height
+    #[arg(long, value_parser = checked(scene::check_positive))]
+    aspect_ratio: Option<f64>,
+
+    /// Samples per pixel
+    #[arg(long, value_parser = checked(scene::check_at_least_one))]
+    spp: Option<i32>,
+
+    /// Relative error at which pixels stop sampling, enables adaptive sampling
+    #[arg(long, value_parser = checked(scene::check_positive))]
+    noise_threshold: Option<f64>,
+
+    /// Samples taken for every pixel before adaptive sampling may stop, needs --noise-threshold
+    /// or adaptive sampling in the scene file
+    #[arg(long, value_parser = checked(scene::check_at_least_one))]
+    min_spp: Option<i32>,
+
+    /// Also write an image of the samples taken for each pixel, from blue (few) to yellow
+    #[arg(long)]
+    heatmap: Option<PathBuf>,
+
+    /// Maximum number of ray bounces
+    #[arg(long, value_parser = checked(scene::check_at_least_one))]
+    depth: Option<i32>,
+
+    /// Bounces before Russian roulette may end dim paths
+    #[arg(long, value_parser = checked(scene::check_not_negative))]
+    roulette_depth: Option<i32>,
+
+    /// Vertical field of view in degrees
+    #[arg(long, value_parser = checked(scene::check_vfov))]
+    vfov: Option<i32>,
+
+    /// Defocus angle in degrees, 0 disables depth of field
+    #[arg(long, value_parser = checked(scene::check_defocus_angle))]
+    aperture: Option<f64>,
+
+    /// Distance from the camera to the plane of perfect focus
+    #[arg(long, value_parser = checked(scene::check_positive))]
+    focus_dist: Option<f64>,
+
+    /// Camera position, as x,y,z
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    lookfrom: Option<Vec3>,
+
+    /// Point the camera looks at, as x,y,z
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    lookat: Option<Vec3>,
+
+    /// Camera up direction, as x,y,z
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    vup: Option<Vec3>,
+
+    /// Color of rays that leave the scene, as r,g,b [default: sky gradient]
+    #[arg(long, value_parser = parse_vec3)]
+    background: Option<Vec3>,
+
+    /// Integrator estimating the light along camera rays: naive, mis, ao, direct, normals, depth,
+    /// uv or material-id
+    #[arg(long)]
+    integrator: Option<IntegratorKind>,
+
+    /// Number of render threads [default: available parallelism]
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    threads: Option<usize>,
+
+    /// Seed of the random number generators, renders with the same seed are identical
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Size of the square tiles rendered by each job
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+    tile_size: Option<i32>,
+
+    /// Write 16 bits per channel PNG files
+    #[arg(long)]
+    png_16bit: bool,
+
+    /// Write 32-bit float OpenEXR channels instead of half floats
+    #[arg(long)]
+    exr_float: bool,
+}
+
+// Parses a value that must also pass one of the range checks shared with scene files.
+fn checked<T>(
+    check: fn(T) -> Result<(), &'static str>,
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static
+where
+    T: FromStr + Copy + 'static,
+    T::Err: fmt::Display,
+{
+    move |s| {
+        let value = s.parse::<T>().map_err(|e| e.to_string())?;
+        check(value).map_err(|message| format!("{} {}", s, message))?;
+        Ok(value)
+    }
+}
+
+fn parse_vec3(s: &str) -> Result<Vec3, String> {
+    let values = s
+        .split(',')
+        .map(|v| v.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|e| format!("invalid vector '{}': {}", s, e))?;
+
+    match values[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("expected 3 comma-separated values, got '{}'", s)),
+    }
+}
+
+fn main() -> io::Result<()> {
     colog::init();
 
-    //World
-    let mut world = HittableList::new();
-    let material_ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
-
-    world.add(Sphere::<Lambertian>::new(
-        Point3::new(0., -1000., 0.),
-        1000.0,
-        material_ground,
-    ));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat = random_f64();
-            let center = Point3::new(
-                a as f64 + 0.9 * random_f64(),
-                0.2,
-                b as f64 + 0.9 * random_f64(),
-            );
-
-            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                if choose_mat < 0.8 {
-                    let albedo = Color::random() * Color::random();
-
-                    let sphere_material = Lambertian::new(albedo);
-                    world.add(Sphere::new(center, 0.2, sphere_material));
-                } else if choose_mat < 0.95 {
-                    let albedo = Color::random();
-                    let fuzz = random_f64_bounded(0.0, 0.5);
-                    let sphere_material = Metal::new(albedo, fuzz);
-                    world.add(Sphere::new(center, 0.2, sphere_material));
-                } else {
-                    let sphere_material = Dielectric::new(1.5);
-                    world.add(Sphere::new(center, 0.2, sphere_material));
-                }
-            }
+    let args = Args::parse();
+
+    let scene = if let Some(scene) = scene::builtin(&args.scene, args.seed) {
+        scene
+    } else if args.scene.ends_with(".toml") {
+        scene_file::load(Path::new(&args.scene)).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        })
+    } else {
+        eprintln!(
+            "unknown scene '{}', expected a TOML file or one of: {}",
+            args.scene,
+            scene::BUILTIN_SCENES.join(", ")
+        );
+        process::exit(2);
+    };
+
+    let mut settings = scene.camera;
+    settings.image_width = args.width.unwrap_or(settings.image_width);
+    settings.aspect_ratio = args.aspect_ratio.unwrap_or(settings.aspect_ratio);
+    settings.samples_per_pixel = args.spp.unwrap_or(settings.samples_per_pixel);
+    settings.max_depth = args.depth.unwrap_or(settings.max_depth);
+    settings.roulette_depth = args.roulette_depth.unwrap_or(settings.roulette_depth);
+    settings.vfov = args.vfov.unwrap_or(settings.vfov);
+    settings.defocus_angle = args.aperture.unwrap_or(settings.defocus_angle);
+    settings.focus_dist = args.focus_dist.unwrap_or(settings.focus_dist);
+    settings.lookfrom = args.lookfrom.unwrap_or(settings.lookfrom);
+    settings.lookat = args.lookat.unwrap_or(settings.lookat);
+    settings.vup = args.vup.unwrap_or(settings.vup);
+    settings.background = args.background.or(settings.background);
+    settings.integrator = args.integrator.unwrap_or(settings.integrator);
+    if let Some(threshold) = args.noise_threshold {
+        let adaptive = settings
+            .adaptive
+            .get_or_insert(AdaptiveSampling::new(threshold));
+        adaptive.threshold = threshold;
+    }
+    if let Some(min_spp) = args.min_spp {
+        let Some(adaptive) = &mut settings.adaptive else {
+            eprintln!("--min-spp needs adaptive sampling, enable it with --noise-threshold");
+            process::exit(2);
+        };
+        adaptive.min_samples = min_spp;
+    }
+
+    let mut camera = settings.build();
+    camera.threads = args.threads.unwrap_or(camera.threads);
+    camera.tile_size = args.tile_size.unwrap_or(camera.tile_size);
+    camera.seed = args.seed;
+
+    let options = OutputOptions {
+        png_bit_depth: if args.png_16bit {
+            PngBitDepth::Sixteen
+        } else {
+            PngBitDepth::Eight
+        },
+        exr_pixel_type: if args.exr_float {
+            ExrPixelType::Float
+        } else {
+            ExrPixelType::Half
+        },
+    };
+
+    // Fail on an unsupported output format before spending time on the render.
+    let output = match args.output {
+        Some(path) => {
+            let encoder = encoder::for_path(&path, options)?;
+            Some((path, encoder))
+        }
+        None => None,
+    };
+    let heatmap = match args.heatmap {
+        Some(path) => {
+            let encoder = encoder::for_path(&path, options)?;
+            Some((path, encoder))
         }
+        None => None,
+    };
+
+    if !scene.lights.is_empty() {
+        camera.lights = Some(Arc::new(scene.lights));
     }
 
-    let material1 = Dielectric::new(1.5);
-    world.add(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1));
-
-    let material2 = Lambertian::new(Color::new(0.4, 0.2, 0.1));
-    world.add(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2));
-
-    let material3 = Metal::new(Color::new(0.7, 0.6, 0.5), 0.0);
-    world.add(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3));
-
-    let aspect_ratio = 16.0 / 9.0;
-    let image_width: i32 = 1200;
-    let samples_per_pixel = 500;
-    let max_depth = 50;
-
-    let vfov = 20;
-    let lookfrom = Point3::new(13.0, 2.0, 3.0);
-    let lookat = Point3::new(0.0, 0.0, -0.0);
-    let vup = Vec3::new(0.0, 1.0, 0.0);
-
-    let camera = Camera::new(
-        image_width,
-        aspect_ratio,
-        samples_per_pixel,
-        max_depth,
-        vfov,
-        0.6,
-        10.0,
-        lookfrom,
-        lookat,
-        vup,
+    let world = BvhNode::new(scene.world);
+    let (image, sample_counts) = camera
+        .render_with_sample_counts(Arc::new(world))
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        });
+
+    let total: i64 = sample_counts.iter().map(|&samples| samples as i64).sum();
+    info!(
+        "Took {:.1} samples per pixel on average",
+        total as f64 / sample_counts.len().max(1) as f64
     );
+    if let Some((path, encoder)) = heatmap {
+        encoder::save(
+            &camera.sample_heatmap(&sample_counts),
+            &path,
+            encoder.as_ref(),
+        )?;
+        info!("Wrote {}", path.display());
+    }
+
+    if let Some((path, encoder)) = output {
+        encoder::save(&image, &path, encoder.as_ref())?;
+        info!("Wrote {}", path.display());
+    } else {
+        let mut out = BufWriter::new(io::stdout().lock());
+        PpmEncoder::new(PpmFormat::Ascii).encode(&image, &mut out)?;
+        out.flush()?;
+    }
 
-    camera.render(&world);
     info!("Done");
+    Ok(())
 }
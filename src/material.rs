@@ -1,48 +1,171 @@
-use crate::{color::Color, hittable::HitRecord, ray::Ray, rtweekend::random_f64, vec3::Vec3};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::Arc;
 
-pub trait Material {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)>;
+use crate::{
+    color::Color,
+    hittable::{HitRecord, Hittable},
+    pdf::{CosinePdf, FuzzPdf, Pdf, SpherePdf},
+    ray::Ray,
+    rtweekend::{random_f64, Rng},
+    texture::{SolidColor, Texture},
+    vec3::Point3,
+};
+
+// How a material scatters an incoming ray, see `Material::scatter`.
+pub struct ScatterRecord {
+    pub attenuation: Color,
+    pub direction: ScatterDirection,
+}
+
+pub enum ScatterDirection {
+    // Mirrors and glass send the ray in a single direction, which is followed as is.
+    Specular(Ray),
+    // Other materials scatter over a distribution of directions, importance sampled with this
+    // density and weighted by `Material::scatter_pdf`.
+    Pdf(Box<dyn Pdf>),
+}
+
+impl ScatterRecord {
+    pub fn specular(attenuation: Color, ray: Ray) -> Self {
+        ScatterRecord {
+            attenuation,
+            direction: ScatterDirection::Specular(ray),
+        }
+    }
+
+    pub fn pdf(attenuation: Color, pdf: impl Pdf + 'static) -> Self {
+        ScatterRecord {
+            attenuation,
+            direction: ScatterDirection::Pdf(Box::new(pdf)),
+        }
+    }
+}
+
+pub trait Material: Send + Sync {
+    // Returns None when the ray is absorbed.
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterRecord>;
+
+    // Density of the material scattering `r_in` into `scattered`, for materials that return a
+    // `ScatterDirection::Pdf`.
+    fn scatter_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    // Light given off at the hit point, added to whatever is scattered.
+    fn emitted(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    // Address telling materials apart, the same through every handle of a shared material. It
+    // changes from run to run, see `MaterialNumbers` for numbers that do not.
+    fn id(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
+}
+
+// Numbers the materials of a scene in the order `Hittable::materials` finds them, so the same
+// scene gets the same numbers on every run, wherever its materials end up in memory.
+#[derive(Default)]
+pub struct MaterialNumbers {
+    numbers: HashMap<usize, usize>, // Number of each material id
+}
+
+impl MaterialNumbers {
+    pub fn new(world: &dyn Hittable) -> Self {
+        let mut numbers = HashMap::new();
+        world.materials(&mut |material| {
+            let next = numbers.len();
+            numbers.entry(material.id()).or_insert(next);
+        });
+        MaterialNumbers { numbers }
+    }
+
+    // None for materials that are not in the scene.
+    pub fn get(&self, material: &dyn Material) -> Option<usize> {
+        self.numbers.get(&material.id()).copied()
+    }
+}
+
+// Lets a single material be shared by several objects.
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterRecord> {
+        self.as_ref().scatter(r_in, rec, rng)
+    }
+
+    fn scatter_pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.as_ref().scatter_pdf(r_in, rec, scattered)
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Color {
+        self.as_ref().emitted(u, v, p)
+    }
+
+    fn id(&self) -> usize {
+        self.as_ref().id()
+    }
 }
 
 pub struct Lambertian {
-    albedo: Color,
+    texture: Arc<dyn Texture>,
 }
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
-        Lambertian { albedo }
+        Lambertian::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture>) -> Self {
+        Lambertian { texture }
     }
 }
 impl Material for Lambertian {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
-        let mut scatter_direction = rec.normal + Vec3::random_unit();
-        if scatter_direction.near_zero() {
-            scatter_direction = rec.normal;
-        }
-        let scattered = Ray::new(rec.p, scatter_direction);
-        Some((scattered, self.albedo))
+    fn scatter(&self, _r_in: &Ray, rec: &HitRecord, _rng: &mut Rng) -> Option<ScatterRecord> {
+        let attenuation = self.texture.value(rec.u, rec.v, rec.p);
+        Some(ScatterRecord::pdf(attenuation, CosinePdf::new(rec.normal)))
+    }
+
+    fn scatter_pdf(&self, _r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cos_theta = rec.normal.dot(scattered.direction().unit());
+        f64::max(0.0, cos_theta / PI)
     }
 }
 
 pub struct Metal {
-    albedo: Color,
+    texture: Arc<dyn Texture>,
     fuzz: f64,
 }
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
-        Metal { albedo, fuzz }
+        Metal::from_texture(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture>, fuzz: f64) -> Self {
+        Metal { texture, fuzz }
     }
 }
 impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
-        let mut reflected = r_in.direction().reflect(rec.normal);
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, _rng: &mut Rng) -> Option<ScatterRecord> {
+        // A fuzzy reflection is a lobe around the mirror direction; directions below the
+        // surface are absorbed through `scatter_pdf`.
+        let reflected = r_in.direction().reflect(rec.normal).unit();
+        let attenuation = self.texture.value(rec.u, rec.v, rec.p);
 
-        reflected = reflected.unit() + (Vec3::random_unit() * self.fuzz);
-        let scattered = Ray::new(rec.p, reflected);
+        if self.fuzz <= 0.0 {
+            let scattered = Ray::with_time(rec.p, reflected, r_in.time());
+            return Some(ScatterRecord::specular(attenuation, scattered));
+        }
+        Some(ScatterRecord::pdf(
+            attenuation,
+            FuzzPdf::new(reflected, self.fuzz),
+        ))
+    }
 
-        if scattered.direction().dot(rec.normal) > 0.0 {
-            return Some((scattered, self.albedo));
+    fn scatter_pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        if scattered.direction().dot(rec.normal) <= 0.0 {
+            return 0.0;
         }
-        return None;
+        let reflected = r_in.direction().reflect(rec.normal).unit();
+        FuzzPdf::new(reflected, self.fuzz).value(scattered.direction())
     }
 }
 
@@ -58,15 +181,15 @@ impl Dielectric {
         // Use Schlick's approximation for reflectance.
         let mut r0 = (1.0 - refraction_index) / (1.0 + refraction_index);
         r0 = r0 * r0;
-        return r0 + (1.0 - r0) * f64::powf((1.0 - cosine), 5.0);
+        r0 + (1.0 - r0) * f64::powf(1.0 - cosine, 5.0)
     }
 }
 impl Material for Dielectric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterRecord> {
         let attenuation = Color::new(1.0, 1.0, 1.0);
         let mut ri = self.refraction_index;
         if rec.front_face {
-            ri = (1.0 / self.refraction_index);
+            ri = 1.0 / self.refraction_index;
         };
 
         let unit_direction = r_in.direction().unit();
@@ -77,11 +200,58 @@ impl Material for Dielectric {
         let cannot_refract = ri * sin_theta > 1.0;
         let mut direction = unit_direction.refract(rec.normal, ri);
 
-        if cannot_refract || self.reflectance(cos_theta, ri) > random_f64() {
+        if cannot_refract || self.reflectance(cos_theta, ri) > random_f64(rng) {
             direction = unit_direction.reflect(rec.normal);
         }
 
-        let scattered: Ray = Ray::new(rec.p, direction);
-        return Some((scattered, attenuation));
+        let scattered: Ray = Ray::with_time(rec.p, direction, r_in.time());
+        Some(ScatterRecord::specular(attenuation, scattered))
+    }
+}
+
+// Emits light in every direction and absorbs whatever hits it.
+pub struct DiffuseLight {
+    texture: Arc<dyn Texture>,
+}
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        DiffuseLight::from_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture>) -> Self {
+        DiffuseLight { texture }
+    }
+}
+impl Material for DiffuseLight {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord, _rng: &mut Rng) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Color {
+        self.texture.value(u, v, p)
+    }
+}
+
+// Phase function of participating media: scatters uniformly in every direction.
+pub struct Isotropic {
+    texture: Arc<dyn Texture>,
+}
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Isotropic::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture>) -> Self {
+        Isotropic { texture }
+    }
+}
+impl Material for Isotropic {
+    fn scatter(&self, _r_in: &Ray, rec: &HitRecord, _rng: &mut Rng) -> Option<ScatterRecord> {
+        let attenuation = self.texture.value(rec.u, rec.v, rec.p);
+        Some(ScatterRecord::pdf(attenuation, SpherePdf))
+    }
+
+    fn scatter_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * PI)
     }
 }
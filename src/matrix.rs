@@ -0,0 +1,577 @@
+use std::ops;
+
+use crate::aabb::{self, Aabb};
+use crate::interval::Interval;
+use crate::rtweekend::degrees_to_radians;
+use crate::vec3::{Point3, Vec3};
+
+// Affine transform as a row-major 4x4 matrix, applied to column vectors: the last column holds
+// the translation and the last row is always (0, 0, 0, 1).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f64; 4]; 4],
+}
+
+pub const IDENTITY: Mat4 = Mat4 {
+    m: [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ],
+};
+
+impl Mat4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Mat4 { m }
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let mut t = IDENTITY;
+        for i in 0..3 {
+            t.m[i][3] = offset[i];
+        }
+        t
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        let mut s = IDENTITY;
+        for i in 0..3 {
+            s.m[i][i] = factors[i];
+        }
+        s
+    }
+
+    // Counterclockwise rotations by `angle` degrees, looking down the axis towards the origin.
+    pub fn rotation_x(angle: f64) -> Self {
+        Mat4::rotation(1, 2, angle)
+    }
+
+    pub fn rotation_y(angle: f64) -> Self {
+        Mat4::rotation(2, 0, angle)
+    }
+
+    pub fn rotation_z(angle: f64) -> Self {
+        Mat4::rotation(0, 1, angle)
+    }
+
+    fn rotation(a: usize, b: usize, angle: f64) -> Self {
+        // Rotates axis a towards axis b.
+        let radians = degrees_to_radians(angle);
+        let (sin_theta, cos_theta) = (f64::sin(radians), f64::cos(radians));
+        let mut r = IDENTITY;
+        r.m[a][a] = cos_theta;
+        r.m[a][b] = -sin_theta;
+        r.m[b][a] = sin_theta;
+        r.m[b][b] = cos_theta;
+        r
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = IDENTITY;
+        for (i, row) in t.m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        t
+    }
+
+    pub fn determinant(&self) -> f64 {
+        // Of the upper 3x3 block, the linear part of an affine transform.
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination with partial pivoting; None if the matrix is singular.
+        let mut a = self.m;
+        let mut inv = IDENTITY.m;
+
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| f64::abs(a[i][col]).total_cmp(&f64::abs(a[j][col])))
+                .unwrap();
+            if f64::abs(a[pivot][col]) < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+
+            for row in 0..4 {
+                if row != col {
+                    let factor = a[row][col];
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+
+        Some(Mat4 { m: inv })
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        self.transform_vector(p) + Vec3::new(self.m[0][3], self.m[1][3], self.m[2][3])
+    }
+
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let row = |i: usize| self.m[i][0] * v.x() + self.m[i][1] * v.y() + self.m[i][2] * v.z();
+        Vec3::new(row(0), row(1), row(2))
+    }
+
+    pub fn transform_bbox(&self, bbox: Aabb) -> Aabb {
+        // Returns the box bounding the transformed corners of `bbox`. Each output interval is
+        // built from the extreme contribution of every input axis, so infinite boxes stay
+        // well-defined as long as the matrix does not mix them into other axes.
+        if bbox.x.min > bbox.x.max || bbox.y.min > bbox.y.max || bbox.z.min > bbox.z.max {
+            return aabb::EMPTY;
+        }
+
+        let axis = |i: usize| {
+            let mut min = self.m[i][3];
+            let mut max = self.m[i][3];
+            for j in 0..3 {
+                let factor = self.m[i][j];
+                if factor == 0.0 {
+                    continue;
+                }
+                let interval = bbox.axis_interval(j);
+                let (a, b) = (factor * interval.min, factor * interval.max);
+                min += f64::min(a, b);
+                max += f64::max(a, b);
+            }
+            Interval::new(min, max)
+        };
+        Aabb::new(axis(0), axis(1), axis(2))
+    }
+}
+
+// Motion from the transform `start` at time 0 to `end` at time 1. Each matrix is split into a
+// translation, a rotation and a stretch (scale and shear), which are interpolated separately, the
+// rotation along the shortest arc, so spinning objects keep their shape. The inverse at any time
+// is built from the inverted parts, which stay invertible all along the motion.
+#[derive(Copy, Clone, Debug)]
+pub struct Motion {
+    start: Parts,
+    end: Parts,
+    angle: f64,                    // Radians turned between the start and the end
+    stretch_inverse: Option<Mat4>, // Inverse of the stretch when it does not change
+}
+
+// Steps at which the swept box of a rotating motion is sampled.
+const MOTION_STEPS: usize = 128;
+
+impl Motion {
+    // Panics if `start` or `end` is not invertible, or if only one of them mirrors: the stretch
+    // would then pass through a flat, singular matrix on the way.
+    pub fn new(start: Mat4, end: Mat4) -> Self {
+        let start = Parts::new(start);
+        let mut end = Parts::new(end);
+        // Each stretch is positive definite, or negative definite for a mirror, and so is any
+        // blend of two stretches of the same kind.
+        assert!(
+            (start.stretch.determinant() > 0.0) == (end.stretch.determinant() > 0.0),
+            "transform motion cannot turn into its mirror image"
+        );
+        // q and -q are the same rotation, pick the one on the short arc from the start.
+        if start.rotation.dot(end.rotation) < 0.0 {
+            end.rotation = end.rotation.scale(-1.0);
+        }
+        let angle = 2.0 * f64::acos(f64::min(1.0, start.rotation.dot(end.rotation)));
+        let stretch_inverse = if start.stretch == end.stretch {
+            start.stretch.inverse()
+        } else {
+            None
+        };
+        Motion {
+            start,
+            end,
+            angle,
+            stretch_inverse,
+        }
+    }
+
+    pub fn at(&self, time: f64) -> Mat4 {
+        let (translation, rotation, stretch) = self.parts_at(time);
+        Mat4::translation(translation) * rotation * stretch
+    }
+
+    // Returns the transform at `time` and its inverse.
+    pub fn at_with_inverse(&self, time: f64) -> (Mat4, Mat4) {
+        let (translation, rotation, stretch) = self.parts_at(time);
+        let stretch_inverse = self.stretch_inverse.unwrap_or_else(|| {
+            stretch
+                .inverse()
+                .expect("interpolated stretch is invertible")
+        });
+        (
+            Mat4::translation(translation) * rotation * stretch,
+            stretch_inverse * rotation.transpose() * Mat4::translation(-translation),
+        )
+    }
+
+    fn parts_at(&self, time: f64) -> (Vec3, Mat4, Mat4) {
+        let lerp = |a: f64, b: f64| a + time * (b - a);
+        let (start, end) = (&self.start, &self.end);
+
+        let mut stretch = start.stretch;
+        for (row, end_row) in stretch.m.iter_mut().zip(end.stretch.m) {
+            for (value, end_value) in row.iter_mut().zip(end_row) {
+                *value = lerp(*value, end_value);
+            }
+        }
+        let translation = start.translation + time * (end.translation - start.translation);
+
+        (
+            translation,
+            start.rotation.slerp(end.rotation, time).matrix(),
+            stretch,
+        )
+    }
+
+    pub fn transform_bbox(&self, bbox: Aabb) -> Aabb {
+        // Translation and stretch move every point along a line, but rotation swings it along
+        // an arc. Between two samples a point strays at most `step * radius` from the rotation at
+        // the earlier sample, where radius bounds its distance from the rotation center, so the
+        // sampled boxes padded by twice that bound the whole motion.
+        if bbox.x.min > bbox.x.max || bbox.y.min > bbox.y.max || bbox.z.min > bbox.z.max {
+            return aabb::EMPTY;
+        }
+
+        let steps = if self.angle > 0.0 { MOTION_STEPS } else { 1 };
+        let mut swept = aabb::EMPTY;
+        for i in 0..=steps {
+            let time = i as f64 / steps as f64;
+            swept = Aabb::surrounding(swept, self.at(time).transform_bbox(bbox));
+        }
+        if steps == 1 {
+            return swept;
+        }
+
+        let mut radius: f64 = 0.0;
+        for stretch in [self.start.stretch, self.end.stretch] {
+            for i in 0..8 {
+                let corner = Point3::new(
+                    if i & 1 == 0 { bbox.x.min } else { bbox.x.max },
+                    if i & 2 == 0 { bbox.y.min } else { bbox.y.max },
+                    if i & 4 == 0 { bbox.z.min } else { bbox.z.max },
+                );
+                radius = f64::max(radius, stretch.transform_vector(corner).length());
+            }
+        }
+        let padding = 4.0 * self.angle / steps as f64 * radius;
+        Aabb::new(
+            swept.x.expand(padding),
+            swept.y.expand(padding),
+            swept.z.expand(padding),
+        )
+    }
+}
+
+// An invertible transform as translation * rotation * stretch, where stretch is symmetric.
+#[derive(Copy, Clone, Debug)]
+struct Parts {
+    translation: Vec3,
+    rotation: Quat,
+    stretch: Mat4,
+}
+
+impl Parts {
+    fn new(m: Mat4) -> Self {
+        let translation = Vec3::new(m.m[0][3], m.m[1][3], m.m[2][3]);
+        let mut linear = m;
+        for i in 0..3 {
+            linear.m[i][3] = 0.0;
+        }
+
+        // Polar decomposition: averaging a matrix with its inverse transpose converges to the
+        // closest orthogonal matrix.
+        let mut rotation = linear;
+        for _ in 0..100 {
+            let inverse = rotation
+                .inverse()
+                .expect("transform matrix is not invertible");
+            let mut next = rotation;
+            let mut change: f64 = 0.0;
+            for i in 0..3 {
+                for j in 0..3 {
+                    next.m[i][j] = 0.5 * (rotation.m[i][j] + inverse.m[j][i]);
+                    change = f64::max(change, f64::abs(next.m[i][j] - rotation.m[i][j]));
+                }
+            }
+            rotation = next;
+            if change < 1e-12 {
+                break;
+            }
+        }
+        // A mirroring transform becomes a proper rotation with a negative stretch.
+        if rotation.determinant() < 0.0 {
+            for row in rotation.m.iter_mut().take(3) {
+                for value in row.iter_mut().take(3) {
+                    *value = -*value;
+                }
+            }
+        }
+
+        Parts {
+            translation,
+            rotation: Quat::from_matrix(&rotation),
+            stretch: rotation.transpose() * linear,
+        }
+    }
+}
+
+// Unit quaternion w + xi + yj + zk, for interpolating rotations.
+#[derive(Copy, Clone, Debug)]
+struct Quat {
+    w: f64,
+    v: Vec3,
+}
+
+impl Quat {
+    fn from_matrix(r: &Mat4) -> Self {
+        // Divides by the largest of the four possible denominators, for precision.
+        let m = &r.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let q = if trace > 0.0 {
+            let s = 2.0 * f64::sqrt(trace + 1.0);
+            Quat {
+                w: 0.25 * s,
+                v: Vec3::new(m[2][1] - m[1][2], m[0][2] - m[2][0], m[1][0] - m[0][1]) / s,
+            }
+        } else {
+            let i = (0..3).max_by(|&a, &b| m[a][a].total_cmp(&m[b][b])).unwrap();
+            let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+            let s = 2.0 * f64::sqrt(1.0 + m[i][i] - m[j][j] - m[k][k]);
+            let mut v = [0.0; 3];
+            v[i] = 0.25 * s;
+            v[j] = (m[j][i] + m[i][j]) / s;
+            v[k] = (m[k][i] + m[i][k]) / s;
+            Quat {
+                w: (m[k][j] - m[j][k]) / s,
+                v: Vec3::new(v[0], v[1], v[2]),
+            }
+        };
+        q.scale(1.0 / f64::sqrt(q.dot(q)))
+    }
+
+    fn matrix(&self) -> Mat4 {
+        let (w, x, y, z) = (self.w, self.v.x(), self.v.y(), self.v.z());
+        Mat4::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    fn dot(&self, other: Quat) -> f64 {
+        self.w * other.w + self.v.dot(other.v)
+    }
+
+    fn scale(&self, factor: f64) -> Quat {
+        Quat {
+            w: factor * self.w,
+            v: factor * self.v,
+        }
+    }
+
+    fn add(&self, other: Quat) -> Quat {
+        Quat {
+            w: self.w + other.w,
+            v: self.v + other.v,
+        }
+    }
+
+    fn slerp(&self, other: Quat, t: f64) -> Quat {
+        let cos_theta = f64::min(1.0, self.dot(other));
+        if cos_theta > 0.9995 {
+            // Nearly parallel: a normalized linear blend is accurate and avoids dividing by ~0.
+            let q = self.scale(1.0 - t).add(other.scale(t));
+            return q.scale(1.0 / f64::sqrt(q.dot(q)));
+        }
+        let theta = f64::acos(cos_theta);
+        let sin_theta = f64::sin(theta);
+        self.scale(f64::sin((1.0 - t) * theta) / sin_theta)
+            .add(other.scale(f64::sin(t * theta) / sin_theta))
+    }
+}
+
+impl ops::Mul<Mat4> for Mat4 {
+    type Output = Mat4;
+    fn mul(self, other: Mat4) -> Mat4 {
+        let mut product = Mat4 { m: [[0.0; 4]; 4] };
+        for i in 0..4 {
+            for j in 0..4 {
+                product.m[i][j] = (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+
+        assert_near(Mat4::rotation_x(90.0).transform_vector(y), z);
+        assert_near(Mat4::rotation_y(90.0).transform_vector(z), x);
+        assert_near(Mat4::rotation_z(90.0).transform_vector(x), y);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let m = Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation_y(30.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 1.0));
+        let inverse = m.inverse().unwrap();
+
+        let p = Point3::new(0.3, 4.0, -1.5);
+        assert_near(inverse.transform_point(m.transform_point(p)), p);
+        for (row, expected) in (m * inverse).m.iter().zip(IDENTITY.m) {
+            for (value, expected) in row.iter().zip(expected) {
+                assert!(f64::abs(value - expected) < 1e-12);
+            }
+        }
+
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn test_motion() {
+        let start = Mat4::translation(Vec3::new(0.0, 0.0, 0.0));
+        let end = Mat4::translation(Vec3::new(4.0, 0.0, 0.0))
+            * Mat4::rotation_y(90.0)
+            * Mat4::scaling(Vec3::new(3.0, 1.0, 1.0));
+        let motion = Motion::new(start, end);
+
+        for (time, expected) in [(0.0, start), (1.0, end)] {
+            let m = motion.at(time);
+            for (row, expected) in m.m.iter().zip(expected.m) {
+                for (value, expected) in row.iter().zip(expected) {
+                    assert!(f64::abs(value - expected) < 1e-9, "{:?}", m);
+                }
+            }
+        }
+
+        // Halfway the object has turned by 45 degrees and is stretched by 2, without shrinking.
+        let halfway = Mat4::translation(Vec3::new(2.0, 0.0, 0.0))
+            * Mat4::rotation_y(45.0)
+            * Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let p = Point3::new(1.0, 1.0, 1.0);
+        assert_near(
+            motion.at(0.5).transform_point(p),
+            halfway.transform_point(p),
+        );
+
+        // Mirrored transforms keep their mirror.
+        let mirror = Mat4::scaling(Vec3::new(-1.0, 1.0, 1.0));
+        let motion = Motion::new(mirror, mirror * Mat4::rotation_z(30.0));
+        assert_near(motion.at(0.0).transform_point(p), mirror.transform_point(p));
+        assert_near(
+            motion.at(0.5).transform_point(p),
+            (mirror * Mat4::rotation_z(15.0)).transform_point(p),
+        );
+    }
+
+    #[test]
+    fn test_motion_inverse() {
+        let start = Mat4::scaling(Vec3::new(0.5, 1.0, 1.0));
+        let end = Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation_x(120.0)
+            * Mat4::scaling(Vec3::new(4.0, 2.0, 0.25));
+        let p = Point3::new(0.3, 4.0, -1.5);
+        for motion in [Motion::new(start, end), Motion::new(end, end * start)] {
+            for i in 0..=10 {
+                let (m, inverse) = motion.at_with_inverse(i as f64 / 10.0);
+                assert_near(inverse.transform_point(m.transform_point(p)), p);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "mirror image")]
+    fn test_motion_into_mirror() {
+        // Halfway the x axis would be squashed flat.
+        Motion::new(IDENTITY, Mat4::scaling(Vec3::new(-1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_motion_bbox() {
+        // A box off the rotation axis sweeps an arc, which bulges past the end boxes.
+        let bbox = Aabb::from_points(Point3::new(2.0, 0.0, 0.0), Point3::new(3.0, 1.0, 1.0));
+        let motion = Motion::new(IDENTITY, Mat4::rotation_y(180.0));
+        let swept = motion.transform_bbox(bbox);
+
+        for i in 0..=1000 {
+            let m = motion.at(i as f64 / 1000.0);
+            for corner in [Point3::new(2.0, 0.0, 0.0), Point3::new(3.0, 1.0, 1.0)] {
+                let p = m.transform_point(corner);
+                assert!(
+                    swept.x.contains(p.x()) && swept.z.contains(p.z()),
+                    "{:?}",
+                    p
+                );
+            }
+        }
+        assert!(swept.z.max < 3.5 && swept.z.min > -3.5, "{:?}", swept);
+
+        // Without rotation the end boxes are exact.
+        let motion = Motion::new(IDENTITY, Mat4::translation(Vec3::new(1.0, 0.0, 0.0)));
+        let swept = motion.transform_bbox(bbox);
+        assert!(f64::abs(swept.x.min - 2.0) < 1e-9 && f64::abs(swept.x.max - 4.0) < 1e-9);
+    }
+
+    #[test]
+    fn test_transform_bbox() {
+        let bbox = Aabb::from_points(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let rotated = Mat4::rotation_z(45.0).transform_bbox(bbox);
+        assert!(f64::abs(rotated.x.min + f64::sqrt(0.5)) < 1e-9);
+        assert!(f64::abs(rotated.y.max - f64::sqrt(2.0)) < 1e-9);
+
+        // An infinite plane keeps its finite thickness when moved along its normal.
+        let plane = Aabb::new(
+            crate::interval::UNIVERSE,
+            Interval::new(0.0, 0.0),
+            crate::interval::UNIVERSE,
+        );
+        let moved = Mat4::translation(Vec3::new(0.0, 2.0, 0.0)).transform_bbox(plane);
+        assert!(moved.y.contains(2.0) && moved.y.size() < 0.001);
+        assert_eq!(moved.x.size(), f64::INFINITY);
+    }
+}
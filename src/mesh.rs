@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::bvh::BvhNode;
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::{Lambertian, Material, ScatterRecord};
+use crate::ray::Ray;
+use crate::rtweekend::Rng;
+use crate::texture::{Texture, VertexColorTexture};
+use crate::triangle::Triangle;
+use crate::vec3::{Point3, Vec3};
+
+mod obj;
+mod ply;
+mod stl;
+
+// Materials of a mesh: one per group name, and a default for the faces of any other group.
+#[derive(Clone)]
+pub struct MeshMaterials {
+    default: Arc<dyn Material>,
+    groups: HashMap<String, Arc<dyn Material>>,
+    vertex_colors: Option<Arc<dyn Material>>,
+}
+
+impl MeshMaterials {
+    pub fn new(default: Arc<dyn Material>) -> Self {
+        MeshMaterials {
+            default,
+            groups: HashMap::new(),
+            vertex_colors: None,
+        }
+    }
+
+    pub fn with_group(mut self, name: &str, material: Arc<dyn Material>) -> Self {
+        self.groups.insert(name.to_string(), material);
+        self
+    }
+
+    // Faces whose vertices all have a color become Lambertian, with the colors blended as albedo.
+    // They all share one white Lambertian, which `FaceMaterial` tints.
+    pub fn with_vertex_colors(mut self) -> Self {
+        self.vertex_colors = Some(Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0))));
+        self
+    }
+
+    fn get(&self, group: Option<&str>) -> Arc<dyn Material> {
+        group
+            .and_then(|name| self.groups.get(name))
+            .unwrap_or(&self.default)
+            .clone()
+    }
+}
+
+// Material of a mesh face: one of the mesh materials, or the shared vertex-color material tinted
+// by the colors of the face's corners, which are stored inline so faces need no allocation.
+#[derive(Clone)]
+pub enum FaceMaterial {
+    Shared(Arc<dyn Material>),
+    VertexColors(Arc<dyn Material>, VertexColorTexture),
+}
+
+impl FaceMaterial {
+    fn material(&self) -> &dyn Material {
+        match self {
+            FaceMaterial::Shared(material) | FaceMaterial::VertexColors(material, _) => {
+                material.as_ref()
+            }
+        }
+    }
+}
+
+impl Material for FaceMaterial {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterRecord> {
+        let mut srec = self.material().scatter(r_in, rec, rng)?;
+        if let FaceMaterial::VertexColors(_, colors) = self {
+            srec.attenuation *= colors.value(rec.u, rec.v, rec.p);
+        }
+        Some(srec)
+    }
+
+    fn scatter_pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.material().scatter_pdf(r_in, rec, scattered)
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Color {
+        self.material().emitted(u, v, p)
+    }
+
+    fn id(&self) -> usize {
+        self.material().id()
+    }
+}
+
+// Triangles sharing their own BVH, so a mesh is a single object in the scene's BVH.
+pub struct TriangleMesh {
+    root: BvhNode,
+    len: usize,
+}
+
+impl TriangleMesh {
+    pub fn new(triangles: Vec<Triangle<FaceMaterial>>) -> Self {
+        let len = triangles.len();
+        let mut list = HittableList::new();
+        for triangle in triangles {
+            list.add(triangle);
+        }
+        TriangleMesh {
+            root: BvhNode::new(list),
+            len,
+        }
+    }
+
+    // Number of triangles in the mesh.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn load(path: &Path, materials: &MeshMaterials) -> io::Result<Self> {
+        // Picks the loader from the file extension: obj, ply or stl.
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("obj") => TriangleMesh::load_obj(path, materials),
+            Some("ply") => TriangleMesh::load_ply(path, materials),
+            Some("stl") => TriangleMesh::load_stl(path, materials),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unsupported mesh format '{}', expected obj, ply or stl",
+                    path.display()
+                ),
+            )),
+        }
+    }
+
+    pub fn load_obj(path: &Path, materials: &MeshMaterials) -> io::Result<Self> {
+        TriangleMesh::parse_obj(&fs::read_to_string(path)?, materials)
+    }
+
+    pub fn parse_obj(src: &str, materials: &MeshMaterials) -> io::Result<Self> {
+        obj::parse(src, materials).map(TriangleMesh::new)
+    }
+
+    pub fn load_ply(path: &Path, materials: &MeshMaterials) -> io::Result<Self> {
+        TriangleMesh::parse_ply(&fs::read(path)?, materials)
+    }
+
+    // Reads ASCII and binary (either endianness) PLY data.
+    pub fn parse_ply(data: &[u8], materials: &MeshMaterials) -> io::Result<Self> {
+        ply::parse(data, materials).map(TriangleMesh::new)
+    }
+
+    pub fn load_stl(path: &Path, materials: &MeshMaterials) -> io::Result<Self> {
+        TriangleMesh::parse_stl(&fs::read(path)?, materials)
+    }
+
+    // Reads ASCII and binary STL data.
+    pub fn parse_stl(data: &[u8], materials: &MeshMaterials) -> io::Result<Self> {
+        stl::parse(data, materials).map(TriangleMesh::new)
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        self.root.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.root.bounding_box()
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.root.materials(visit)
+    }
+}
+
+// Mesh vertex, with the optional attributes the file formats may attach to it.
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: Point3,
+    normal: Option<Vec3>,
+    texcoord: Option<(f64, f64)>,
+    color: Option<Color>,
+}
+
+impl Vertex {
+    fn new(position: Point3) -> Self {
+        Vertex {
+            position,
+            normal: None,
+            texcoord: None,
+            color: None,
+        }
+    }
+}
+
+fn triangulate(
+    polygon: &[Vertex],
+    materials: &MeshMaterials,
+    group: Option<&str>,
+    triangles: &mut Vec<Triangle<FaceMaterial>>,
+) {
+    // Splits a convex polygon into a fan of triangles around its first vertex.
+    for i in 1..polygon.len().saturating_sub(1) {
+        if let Some(triangle) = triangle([polygon[0], polygon[i], polygon[i + 1]], materials, group)
+        {
+            triangles.push(triangle);
+        }
+    }
+}
+
+fn triangle(
+    vertices: [Vertex; 3],
+    materials: &MeshMaterials,
+    group: Option<&str>,
+) -> Option<Triangle<FaceMaterial>> {
+    // Returns None for degenerate triangles, which have no normal.
+    let [a, b, c] = vertices.map(|vertex| vertex.position);
+    if (b - a).cross(c - a).length_squared() == 0.0 {
+        return None;
+    }
+
+    // Vertex colors are blended by the barycentric u, v, so those faces take no texcoords.
+    let colors = vertices.map(|vertex| vertex.color);
+    let (material, texcoords) = match (&materials.vertex_colors, colors) {
+        (Some(material), [Some(ca), Some(cb), Some(cc)]) => (
+            FaceMaterial::VertexColors(material.clone(), VertexColorTexture::new([ca, cb, cc])),
+            [None; 3],
+        ),
+        _ => (
+            FaceMaterial::Shared(materials.get(group)),
+            vertices.map(|vertex| vertex.texcoord),
+        ),
+    };
+
+    let mut triangle = Triangle::new(a, b, c, material);
+    if let [Some(na), Some(nb), Some(nc)] = vertices.map(|vertex| vertex.normal) {
+        triangle = triangle.with_normals([na, nb, nc]);
+    }
+    if let [Some(ta), Some(tb), Some(tc)] = texcoords {
+        triangle = triangle.with_uvs([ta, tb, tc]);
+    }
+    Some(triangle)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_by_extension() {
+        let materials = MeshMaterials::new(Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/models/pyramid.obj");
+        assert_eq!(TriangleMesh::load(&path, &materials).unwrap().len(), 6);
+
+        let err = TriangleMesh::load(Path::new("model.fbx"), &materials)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,208 @@
+use std::io;
+
+use super::{invalid_data, triangulate, FaceMaterial, MeshMaterials, Vertex};
+use crate::triangle::Triangle;
+use crate::vec3::{Point3, Vec3};
+
+pub(super) fn parse(
+    src: &str,
+    materials: &MeshMaterials,
+) -> io::Result<Vec<Triangle<FaceMaterial>>> {
+    // Reads the geometry of a Wavefront OBJ file: `v`, `vt` and `vn` records, polygonal `f`
+    // faces and `g` or `o` groups, which select the material of the faces that follow them.
+    // Other records are ignored.
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut texcoords: Vec<(f64, f64)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut group: Option<&str> = None;
+    let mut triangles = Vec::new();
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let tokens: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => positions.push(parse_vec3(&tokens, line_number)?),
+            "vn" => normals.push(parse_vec3(&tokens, line_number)?),
+            "vt" => {
+                // The v coordinate is optional and defaults to 0.
+                let values = parse_floats(&tokens, line_number)?;
+                match values[..] {
+                    [u] => texcoords.push((u, 0.0)),
+                    [u, v, ..] => texcoords.push((u, v)),
+                    _ => return Err(error(line_number, "expected 1 to 3 values")),
+                }
+            }
+            "g" | "o" => group = tokens.first().copied(),
+            "f" => {
+                if tokens.len() < 3 {
+                    return Err(error(line_number, "a face needs 3 vertices"));
+                }
+                let polygon = tokens
+                    .iter()
+                    .map(|token| {
+                        parse_face_vertex(token, &positions, &texcoords, &normals).ok_or_else(
+                            || error(line_number, &format!("invalid face vertex '{}'", token)),
+                        )
+                    })
+                    .collect::<io::Result<Vec<Vertex>>>()?;
+
+                triangulate(&polygon, materials, group, &mut triangles);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn parse_face_vertex(
+    token: &str,
+    positions: &[Point3],
+    texcoords: &[(f64, f64)],
+    normals: &[Vec3],
+) -> Option<Vertex> {
+    // Parses `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    let mut indices = token.split('/');
+    let mut vertex = Vertex::new(positions[resolve_index(indices.next()?, positions.len())?]);
+    vertex.texcoord = match indices.next() {
+        None | Some("") => None,
+        Some(i) => Some(texcoords[resolve_index(i, texcoords.len())?]),
+    };
+    vertex.normal = match indices.next() {
+        None | Some("") => None,
+        Some(i) => Some(normals[resolve_index(i, normals.len())?]),
+    };
+    if indices.next().is_some() {
+        return None;
+    }
+
+    Some(vertex)
+}
+
+fn resolve_index(token: &str, count: usize) -> Option<usize> {
+    // OBJ indices start at 1; negative ones count back from the last element read so far.
+    let index: i64 = token.parse().ok()?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    (0..count as i64)
+        .contains(&resolved)
+        .then_some(resolved as usize)
+}
+
+fn parse_floats(tokens: &[&str], line_number: usize) -> io::Result<Vec<f64>> {
+    tokens
+        .iter()
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|e| error(line_number, &format!("invalid number '{}': {}", token, e)))
+        })
+        .collect()
+}
+
+fn parse_vec3(tokens: &[&str], line_number: usize) -> io::Result<Vec3> {
+    // A fourth (w) coordinate is allowed and ignored.
+    match parse_floats(tokens, line_number)?[..] {
+        [x, y, z] | [x, y, z, _] => Ok(Vec3::new(x, y, z)),
+        _ => Err(error(line_number, "expected 3 values")),
+    }
+}
+
+fn error(line_number: usize, message: &str) -> io::Error {
+    invalid_data(format!("line {}: {}", line_number, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::hittable::Hittable;
+    use crate::interval;
+    use crate::material::Lambertian;
+    use crate::mesh::{MeshMaterials, TriangleMesh};
+    use crate::ray::Ray;
+    use crate::vec3::{Point3, Vec3};
+    use std::sync::Arc;
+
+    fn materials() -> MeshMaterials {
+        MeshMaterials::new(Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn test_parse_obj() {
+        let src = "
+            # Unit square in the z = 0 plane, as a quad and as two triangles
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vt 0 0
+            vt 1 0
+            vt 1 1
+            vt 0 1
+            vn 0 0 1
+            g quad
+            f 1/1/1 2/2/1 3/3/1 4/4/1
+            g triangles
+            s off
+            f -4//1 -3//1 -2//1
+            f 1 3 4
+        ";
+        let mesh = TriangleMesh::parse_obj(src, &materials()).unwrap();
+        assert_eq!(mesh.len(), 4);
+
+        let r = Ray::new(Point3::new(0.75, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(r, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 1.0);
+        assert!(rec.front_face);
+
+        let bbox = mesh.bounding_box();
+        assert!(bbox.x.contains(0.0) && bbox.x.contains(1.0));
+        assert!(bbox.x.size() < 1.001);
+    }
+
+    #[test]
+    fn test_group_materials() {
+        let src = "
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            g red
+            f 1 2 3
+        ";
+        let red = Color::new(1.0, 0.0, 0.0);
+        let materials = materials().with_group("red", Arc::new(Lambertian::new(red)));
+        let mesh = TriangleMesh::parse_obj(src, &materials).unwrap();
+
+        let r = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(r, interval::UNIVERSE).unwrap();
+        let mut rng = crate::rtweekend::seeded_rng(0, 0);
+        let attenuation = rec
+            .material
+            .scatter(&r, &rec, &mut rng)
+            .unwrap()
+            .attenuation;
+        assert_eq!(attenuation.x(), 1.0);
+        assert_eq!(attenuation.y(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = TriangleMesh::parse_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n", &materials())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "line 3: invalid face vertex '3'");
+
+        let err = TriangleMesh::parse_obj("v 0 zero 0\n", &materials())
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("line 1: invalid number 'zero'"));
+    }
+}
@@ -0,0 +1,446 @@
+use std::io;
+use std::str::{self, SplitAsciiWhitespace};
+
+use super::{invalid_data, triangulate, FaceMaterial, MeshMaterials, Vertex};
+use crate::color::{self, Color};
+use crate::triangle::Triangle;
+use crate::vec3::{Point3, Vec3};
+
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone)]
+enum ScalarType {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> io::Result<Self> {
+        Ok(match name {
+            "char" | "int8" => ScalarType::Int8,
+            "uchar" | "uint8" => ScalarType::Uint8,
+            "short" | "int16" => ScalarType::Int16,
+            "ushort" | "uint16" => ScalarType::Uint16,
+            "int" | "int32" => ScalarType::Int32,
+            "uint" | "uint32" => ScalarType::Uint32,
+            "float" | "float32" => ScalarType::Float32,
+            "double" | "float64" => ScalarType::Float64,
+            _ => return Err(invalid_data(format!("PLY: unknown type '{}'", name))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::Uint8 => 1,
+            ScalarType::Int16 | ScalarType::Uint16 => 2,
+            ScalarType::Int32 | ScalarType::Uint32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    fn color_scale(self) -> f64 {
+        // Integer color channels span their whole range, float ones span [0,1].
+        match self {
+            ScalarType::Float32 | ScalarType::Float64 => 1.0,
+            ScalarType::Uint16 | ScalarType::Int16 => 1.0 / 65535.0,
+            _ => 1.0 / 255.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar(ScalarType, String),
+    List(ScalarType, ScalarType, String),
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar(_, name) | Property::List(_, _, name) => name,
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// Element data following the header, read one value at a time.
+enum Body<'a> {
+    Ascii(SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], big_endian: bool },
+}
+
+impl Body<'_> {
+    fn read(&mut self, ty: ScalarType) -> io::Result<f64> {
+        let unexpected_end = || invalid_data("PLY: unexpected end of data".to_string());
+
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens.next().ok_or_else(unexpected_end)?;
+                token
+                    .parse::<f64>()
+                    .map_err(|e| invalid_data(format!("PLY: invalid number '{}': {}", token, e)))
+            }
+            Body::Binary { data, big_endian } => {
+                if data.len() < ty.size() {
+                    return Err(unexpected_end());
+                }
+                let (bytes, rest) = data.split_at(ty.size());
+                *data = rest;
+
+                macro_rules! decode {
+                    ($t:ty) => {{
+                        let bytes = bytes.try_into().unwrap();
+                        (if *big_endian {
+                            <$t>::from_be_bytes(bytes)
+                        } else {
+                            <$t>::from_le_bytes(bytes)
+                        }) as f64
+                    }};
+                }
+                Ok(match ty {
+                    ScalarType::Int8 => decode!(i8),
+                    ScalarType::Uint8 => decode!(u8),
+                    ScalarType::Int16 => decode!(i16),
+                    ScalarType::Uint16 => decode!(u16),
+                    ScalarType::Int32 => decode!(i32),
+                    ScalarType::Uint32 => decode!(u32),
+                    ScalarType::Float32 => decode!(f32),
+                    ScalarType::Float64 => decode!(f64),
+                })
+            }
+        }
+    }
+}
+
+pub(super) fn parse(
+    data: &[u8],
+    materials: &MeshMaterials,
+) -> io::Result<Vec<Triangle<FaceMaterial>>> {
+    // Reads the `vertex` and `face` elements. Vertices may carry normals (nx, ny, nz), texture
+    // coordinates (s, t or u, v) and colors (red, green, blue); other elements and properties are
+    // skipped.
+    let (format, elements, body) = parse_header(data)?;
+    let mut body = match format {
+        Format::Ascii => Body::Ascii(
+            str::from_utf8(body)
+                .map_err(|e| invalid_data(format!("PLY: {}", e)))?
+                .split_ascii_whitespace(),
+        ),
+        _ => Body::Binary {
+            data: body,
+            big_endian: format == Format::BinaryBigEndian,
+        },
+    };
+
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+
+    for element in &elements {
+        let index_of = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|property| names.contains(&property.name()))
+        };
+        let position = [index_of(&["x"]), index_of(&["y"]), index_of(&["z"])];
+        let normal = [index_of(&["nx"]), index_of(&["ny"]), index_of(&["nz"])];
+        let texcoord = [
+            index_of(&["s", "u", "texture_u"]),
+            index_of(&["t", "v", "texture_v"]),
+        ];
+        let color = [
+            index_of(&["red", "diffuse_red"]),
+            index_of(&["green", "diffuse_green"]),
+            index_of(&["blue", "diffuse_blue"]),
+        ];
+        let indices = index_of(&["vertex_indices", "vertex_index"]);
+
+        for _ in 0..element.count {
+            // Scalar values by property index; list values are only kept for faces.
+            let mut values = vec![0.0; element.properties.len()];
+            let mut list = Vec::new();
+            for (i, property) in element.properties.iter().enumerate() {
+                match property {
+                    Property::Scalar(ty, _) => values[i] = body.read(*ty)?,
+                    Property::List(count_type, item_type, _) => {
+                        let count = body.read(*count_type)? as usize;
+                        let items = (0..count)
+                            .map(|_| body.read(*item_type))
+                            .collect::<io::Result<Vec<f64>>>()?;
+                        if Some(i) == indices {
+                            list = items;
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let [Some(x), Some(y), Some(z)] = position else {
+                        return Err(invalid_data("PLY: vertices need x, y and z".to_string()));
+                    };
+                    let mut vertex = Vertex::new(Point3::new(values[x], values[y], values[z]));
+                    if let [Some(nx), Some(ny), Some(nz)] = normal {
+                        vertex.normal = Some(Vec3::new(values[nx], values[ny], values[nz]));
+                    }
+                    if let [Some(s), Some(t)] = texcoord {
+                        vertex.texcoord = Some((values[s], values[t]));
+                    }
+                    if let [Some(r), Some(g), Some(b)] = color {
+                        // Like image textures, stored colors are gamma encoded.
+                        let channel = |i: usize| match &element.properties[i] {
+                            Property::Scalar(ty, _) => {
+                                color::gamma_to_linear(values[i] * ty.color_scale())
+                            }
+                            Property::List(..) => 0.0,
+                        };
+                        vertex.color = Some(Color::new(channel(r), channel(g), channel(b)));
+                    }
+                    vertices.push(vertex);
+                }
+                "face" => faces.push(list),
+                _ => {}
+            }
+        }
+    }
+
+    let mut triangles = Vec::new();
+    for face in faces {
+        let polygon = face
+            .iter()
+            .map(|&index| {
+                // List values are read as floats, so a negative or fractional index would cast
+                // to a different vertex.
+                if index < 0.0 || index.fract() != 0.0 {
+                    return Err(invalid_data(format!("PLY: invalid vertex index {}", index)));
+                }
+                vertices.get(index as usize).copied().ok_or_else(|| {
+                    invalid_data(format!("PLY: face refers to missing vertex {}", index))
+                })
+            })
+            .collect::<io::Result<Vec<Vertex>>>()?;
+        triangulate(&polygon, materials, None, &mut triangles);
+    }
+
+    Ok(triangles)
+}
+
+fn parse_header(data: &[u8]) -> io::Result<(Format, Vec<Element>, &[u8])> {
+    // Returns the format, the element declarations and the data following the header.
+    let end = data
+        .windows(b"end_header".len())
+        .position(|window| window == b"end_header")
+        .ok_or_else(|| invalid_data("PLY: missing end_header".to_string()))?;
+    let body_start = data[end..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(data.len(), |i| end + i + 1);
+    let header = str::from_utf8(&data[..end]).map_err(|e| invalid_data(format!("PLY: {}", e)))?;
+
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err(invalid_data("PLY: missing 'ply' magic number".to_string()));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens[..] {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", name, _version] => {
+                format = Some(match name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(invalid_data(format!("PLY: unknown format '{}'", name))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|e| invalid_data(format!("PLY: invalid count '{}': {}", count, e)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => {
+                let property = Property::List(
+                    ScalarType::parse(count_type)?,
+                    ScalarType::parse(item_type)?,
+                    name.to_string(),
+                );
+                add_property(&mut elements, property)?;
+            }
+            ["property", ty, name] => {
+                let property = Property::Scalar(ScalarType::parse(ty)?, name.to_string());
+                add_property(&mut elements, property)?;
+            }
+            _ => {
+                return Err(invalid_data(format!(
+                    "PLY: invalid header line '{}'",
+                    line.trim()
+                )))
+            }
+        }
+    }
+
+    let format = format.ok_or_else(|| invalid_data("PLY: missing format".to_string()))?;
+    Ok((format, elements, &data[body_start..]))
+}
+
+fn add_property(elements: &mut [Element], property: Property) -> io::Result<()> {
+    match elements.last_mut() {
+        Some(element) => {
+            element.properties.push(property);
+            Ok(())
+        }
+        None => Err(invalid_data(format!(
+            "PLY: property '{}' outside of an element",
+            property.name()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::hittable::Hittable;
+    use crate::interval;
+    use crate::material::Lambertian;
+    use crate::mesh::{MeshMaterials, TriangleMesh};
+    use crate::ray::Ray;
+    use crate::vec3::{Point3, Vec3};
+    use std::sync::Arc;
+
+    fn materials() -> MeshMaterials {
+        MeshMaterials::new(Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    fn header(format: &str) -> String {
+        format!(
+            "ply\nformat {} 1.0\ncomment unit square\nelement vertex 4\nproperty float x\n\
+             property float y\nproperty float z\nproperty uchar red\nproperty uchar green\n\
+             property uchar blue\nelement face 1\nproperty list uchar int vertex_indices\n\
+             end_header\n",
+            format
+        )
+    }
+
+    // Albedo seen by a ray hitting the square at (x, y).
+    fn albedo(mesh: &TriangleMesh, x: f64, y: f64) -> Color {
+        let r = Ray::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(r, interval::UNIVERSE).unwrap();
+        let mut rng = crate::rtweekend::seeded_rng(0, 0);
+        rec.material
+            .scatter(&r, &rec, &mut rng)
+            .unwrap()
+            .attenuation
+    }
+
+    #[test]
+    fn test_parse_ascii() {
+        let src = header("ascii")
+            + "0 0 0 255 0 0\n1 0 0 255 0 0\n1 1 0 0 0 255\n0 1 0 0 0 255\n4 0 1 2 3\n";
+        let mesh = TriangleMesh::parse_ply(src.as_bytes(), &materials()).unwrap();
+        assert_eq!(mesh.len(), 2);
+        assert_eq!(albedo(&mesh, 0.5, 0.5).x(), 0.5);
+
+        // Vertex colors are interpolated across each face, after removing their gamma.
+        let mesh =
+            TriangleMesh::parse_ply(src.as_bytes(), &materials().with_vertex_colors()).unwrap();
+        let bottom = albedo(&mesh, 0.5, 0.0);
+        assert!(f64::abs(bottom.x() - 1.0) < 1e-9 && bottom.z() < 1e-9);
+        let middle = albedo(&mesh, 0.5, 0.5);
+        assert!(f64::abs(middle.x() - 0.5) < 1e-9, "{:?}", middle);
+        assert!(f64::abs(middle.z() - 0.5) < 1e-9, "{:?}", middle);
+
+        // Both faces share the mesh's vertex-color material.
+        let id = |x: f64, y: f64| {
+            let r = Ray::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+            mesh.hit(r, interval::UNIVERSE).unwrap().material.id()
+        };
+        assert_eq!(id(0.75, 0.25), id(0.25, 0.75));
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        let vertices = [
+            [0.0f32, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut data = header(format).into_bytes();
+            for vertex in vertices {
+                for coordinate in vertex {
+                    data.extend(if big_endian {
+                        coordinate.to_be_bytes()
+                    } else {
+                        coordinate.to_le_bytes()
+                    });
+                }
+                data.extend([0, 255, 0]);
+            }
+            data.push(4);
+            for index in 0i32..4 {
+                data.extend(if big_endian {
+                    index.to_be_bytes()
+                } else {
+                    index.to_le_bytes()
+                });
+            }
+
+            let materials = materials().with_vertex_colors();
+            let mesh = TriangleMesh::parse_ply(&data, &materials).unwrap();
+            assert_eq!(mesh.len(), 2, "{}", format);
+            assert_eq!(albedo(&mesh, 0.25, 0.75).y(), 1.0, "{}", format);
+
+            data.truncate(data.len() - 1);
+            assert!(TriangleMesh::parse_ply(&data, &materials).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = TriangleMesh::parse_ply(b"solid\n", &materials())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "PLY: missing end_header");
+
+        let src = header("ascii") + "0 0 0 0 0 0\n0 0 0 0 0 0\n0 0 0 0 0 0\n0 0 0 0 0 0\n3 0 1 9\n";
+        let err = TriangleMesh::parse_ply(src.as_bytes(), &materials())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "PLY: face refers to missing vertex 9");
+
+        for (index, message) in [("-1", "-1"), ("1.5", "1.5"), ("nan", "NaN")] {
+            let src = header("ascii")
+                + "0 0 0 0 0 0\n0 0 0 0 0 0\n0 0 0 0 0 0\n0 0 0 0 0 0\n3 0 "
+                + index
+                + " 2\n";
+            let err = TriangleMesh::parse_ply(src.as_bytes(), &materials())
+                .err()
+                .unwrap();
+            assert_eq!(
+                err.to_string(),
+                format!("PLY: invalid vertex index {}", message)
+            );
+        }
+    }
+}
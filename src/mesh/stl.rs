@@ -0,0 +1,167 @@
+use std::io;
+use std::str;
+
+use super::{invalid_data, triangulate, FaceMaterial, MeshMaterials, Vertex};
+use crate::triangle::Triangle;
+use crate::vec3::Point3;
+
+const HEADER_SIZE: usize = 84; // 80 byte comment and the triangle count
+const FACET_SIZE: usize = 50; // Normal, three vertices and a 2 byte attribute
+
+pub(super) fn parse(
+    data: &[u8],
+    materials: &MeshMaterials,
+) -> io::Result<Vec<Triangle<FaceMaterial>>> {
+    // Facet normals are ignored, faces point to the side where their vertices turn
+    // counterclockwise like they do in every other format.
+    if is_binary(data) {
+        parse_binary(data, materials)
+    } else {
+        let src = str::from_utf8(data).map_err(|e| invalid_data(format!("STL: {}", e)))?;
+        parse_ascii(src, materials)
+    }
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    // ASCII files start with "solid", but so do the comments of some binary ones: trust the
+    // triangle count when it matches the file size.
+    if data.len() >= HEADER_SIZE {
+        let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+        if data.len() == HEADER_SIZE + count * FACET_SIZE {
+            return true;
+        }
+    }
+    !data.trim_ascii_start().starts_with(b"solid")
+}
+
+fn parse_binary(data: &[u8], materials: &MeshMaterials) -> io::Result<Vec<Triangle<FaceMaterial>>> {
+    if data.len() < HEADER_SIZE {
+        return Err(invalid_data("STL: truncated header".to_string()));
+    }
+    let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+    if data.len() < HEADER_SIZE + count * FACET_SIZE {
+        return Err(invalid_data(format!(
+            "STL: expected {} triangles, the file is too short",
+            count
+        )));
+    }
+
+    let mut triangles = Vec::with_capacity(count);
+    for facet in data[HEADER_SIZE..].chunks_exact(FACET_SIZE).take(count) {
+        let float = |i: usize| f32::from_le_bytes(facet[4 * i..4 * i + 4].try_into().unwrap());
+        let vertex = |i: usize| {
+            // Floats 0 to 2 are the facet normal.
+            let base = 3 + 3 * i;
+            Vertex::new(Point3::new(
+                float(base) as f64,
+                float(base + 1) as f64,
+                float(base + 2) as f64,
+            ))
+        };
+        triangulate(
+            &[vertex(0), vertex(1), vertex(2)],
+            materials,
+            None,
+            &mut triangles,
+        );
+    }
+
+    Ok(triangles)
+}
+
+fn parse_ascii(src: &str, materials: &MeshMaterials) -> io::Result<Vec<Triangle<FaceMaterial>>> {
+    // Only `vertex` and `endfacet` matter: the vertices of a facet form a polygon.
+    let mut triangles = Vec::new();
+    let mut polygon = Vec::new();
+
+    let mut tokens = src.split_ascii_whitespace();
+    while let Some(token) = tokens.next() {
+        match token {
+            "vertex" => {
+                let mut coordinate = || {
+                    let token = tokens.next().unwrap_or("");
+                    token.parse::<f64>().map_err(|e| {
+                        invalid_data(format!("STL: invalid vertex coordinate '{}': {}", token, e))
+                    })
+                };
+                polygon.push(Vertex::new(Point3::new(
+                    coordinate()?,
+                    coordinate()?,
+                    coordinate()?,
+                )));
+            }
+            "endfacet" => {
+                triangulate(&polygon, materials, None, &mut triangles);
+                polygon.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::hittable::Hittable;
+    use crate::interval;
+    use crate::material::Lambertian;
+    use crate::mesh::{MeshMaterials, TriangleMesh};
+    use crate::ray::Ray;
+    use crate::vec3::{Point3, Vec3};
+    use std::sync::Arc;
+
+    fn materials() -> MeshMaterials {
+        MeshMaterials::new(Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    fn assert_unit_triangle(mesh: &TriangleMesh) {
+        assert_eq!(mesh.len(), 1);
+        let r = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(r, interval::UNIVERSE).unwrap();
+        assert!(rec.front_face);
+        assert_eq!(rec.t, 1.0);
+    }
+
+    #[test]
+    fn test_parse_ascii() {
+        let src = "
+            solid triangle
+              facet normal 0 0 1
+                outer loop
+                  vertex 0 0 0
+                  vertex 1 0 0
+                  vertex 0 1 0
+                endloop
+              endfacet
+            endsolid triangle
+        ";
+        assert_unit_triangle(&TriangleMesh::parse_stl(src.as_bytes(), &materials()).unwrap());
+
+        let err = TriangleMesh::parse_stl(b"solid\nfacet\nvertex 0 x 0\n", &materials())
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .starts_with("STL: invalid vertex coordinate 'x'"));
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        // A comment starting with "solid" must not be mistaken for an ASCII file.
+        let mut data = b"solid exported by CAD".to_vec();
+        data.resize(80, 0);
+        data.extend(1u32.to_le_bytes());
+        for value in [
+            0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+        ] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend([0, 0]);
+        assert_unit_triangle(&TriangleMesh::parse_stl(&data, &materials()).unwrap());
+
+        data[80] = 2;
+        assert!(TriangleMesh::parse_stl(&data, &materials()).is_err());
+    }
+}
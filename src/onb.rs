@@ -0,0 +1,59 @@
+use crate::vec3::Vec3;
+
+// Orthonormal basis whose w axis is a given direction, used to orient sampled directions.
+#[derive(Copy, Clone, Debug)]
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    pub fn new(n: Vec3) -> Self {
+        let w = n.unit();
+        let a = if f64::abs(w.x()) > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(a).unit();
+        let u = w.cross(v);
+        Onb { axis: [u, v, w] }
+    }
+
+    pub fn u(&self) -> Vec3 {
+        self.axis[0]
+    }
+
+    pub fn v(&self) -> Vec3 {
+        self.axis[1]
+    }
+
+    pub fn w(&self) -> Vec3 {
+        self.axis[2]
+    }
+
+    pub fn transform(&self, v: Vec3) -> Vec3 {
+        // Transform from basis coordinates to local space.
+        (v[0] * self.axis[0]) + (v[1] * self.axis[1]) + (v[2] * self.axis[2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_onb() {
+        for n in [
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-0.3, 0.5, 0.8),
+        ] {
+            let onb = Onb::new(n);
+            assert!((onb.w() - n.unit()).length() < 1e-12);
+            assert!(f64::abs(onb.u().dot(onb.v())) < 1e-12);
+            assert!(f64::abs(onb.u().dot(onb.w())) < 1e-12);
+            assert!(f64::abs(onb.u().length() - 1.0) < 1e-12);
+            assert!((onb.transform(Vec3::new(0.0, 0.0, 1.0)) - onb.w()).length() < 1e-12);
+        }
+    }
+}
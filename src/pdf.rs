@@ -0,0 +1,216 @@
+use std::f64::consts::PI;
+
+use crate::hittable::Hittable;
+use crate::onb::Onb;
+use crate::rtweekend::{random_f64, Rng};
+use crate::vec3::{Point3, Vec3};
+
+// Probability density over directions, used to importance sample scattered rays.
+pub trait Pdf {
+    // Density of `direction`, with respect to solid angle.
+    fn value(&self, direction: Vec3) -> f64;
+
+    // Samples a direction from the distribution; it does not need to be unit length.
+    fn generate(&self, rng: &mut Rng) -> Vec3;
+}
+
+// Uniform over all directions.
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self, rng: &mut Rng) -> Vec3 {
+        Vec3::random_unit(rng)
+    }
+}
+
+// Proportional to the cosine of the angle to `w`, over the hemisphere around it.
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(w: Vec3) -> Self {
+        CosinePdf { uvw: Onb::new(w) }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cosine_theta = direction.unit().dot(self.uvw.w());
+        f64::max(0.0, cosine_theta / PI)
+    }
+
+    fn generate(&self, rng: &mut Rng) -> Vec3 {
+        self.uvw.transform(Vec3::random_cosine_direction(rng))
+    }
+}
+
+// Directions `center + fuzz * u` for u uniform on the unit sphere, i.e. the scattering of a fuzzy
+// `Metal` around its unit reflected direction `center`.
+pub struct FuzzPdf {
+    center: Vec3,
+    fuzz: f64,
+}
+
+impl FuzzPdf {
+    pub fn new(center: Vec3, fuzz: f64) -> Self {
+        FuzzPdf { center, fuzz }
+    }
+}
+
+impl Pdf for FuzzPdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        // The direction meets the sphere of sampled points at t = b -/+ sqrt(discriminant).
+        // Converting the uniform area density of each point found to solid angle gives
+        // t^2 / (4 pi fuzz^2 |cos alpha|), where |cos alpha| = sqrt(discriminant) / fuzz.
+        let d = direction.unit();
+        let b = d.dot(self.center);
+        let discriminant = b * b - self.center.length_squared() + self.fuzz * self.fuzz;
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+
+        let sqrtd = f64::sqrt(discriminant);
+        [b - sqrtd, b + sqrtd]
+            .iter()
+            .filter(|t| **t > 0.0)
+            .map(|t| t * t / (4.0 * PI * self.fuzz * sqrtd))
+            .sum()
+    }
+
+    fn generate(&self, rng: &mut Rng) -> Vec3 {
+        self.center + self.fuzz * Vec3::random_unit(rng)
+    }
+}
+
+// Directions from `origin` towards the objects at `time`, e.g. the lights of a scene.
+pub struct HittablePdf<'a> {
+    objects: &'a dyn Hittable,
+    origin: Point3,
+    time: f64,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(objects: &'a dyn Hittable, origin: Point3, time: f64) -> Self {
+        HittablePdf {
+            objects,
+            origin,
+            time,
+        }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        self.objects.pdf_value(self.origin, direction, self.time)
+    }
+
+    fn generate(&self, rng: &mut Rng) -> Vec3 {
+        self.objects.random(self.origin, self.time, rng)
+    }
+}
+
+// Even blend of two distributions.
+pub struct MixturePdf<'a> {
+    p: [&'a dyn Pdf; 2],
+}
+
+impl<'a> MixturePdf<'a> {
+    pub fn new(p0: &'a dyn Pdf, p1: &'a dyn Pdf) -> Self {
+        MixturePdf { p: [p0, p1] }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        0.5 * self.p[0].value(direction) + 0.5 * self.p[1].value(direction)
+    }
+
+    fn generate(&self, rng: &mut Rng) -> Vec3 {
+        if random_f64(rng) < 0.5 {
+            self.p[0].generate(rng)
+        } else {
+            self.p[1].generate(rng)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::matrix::Mat4;
+    use crate::quad::Quad;
+    use crate::rtweekend::seeded_rng;
+    use crate::sphere::Sphere;
+    use crate::transform::Transform;
+    use std::sync::Arc;
+
+    fn assert_normalized(pdf: &dyn Pdf) {
+        // Integrating the density over the sphere by uniform sampling gives 1.
+        let mut rng = seeded_rng(0, 0);
+        let n = 100000;
+        let integral: f64 = (0..n)
+            .map(|_| pdf.value(Vec3::random_unit(&mut rng)) * 4.0 * PI)
+            .sum::<f64>()
+            / n as f64;
+        assert!(f64::abs(integral - 1.0) < 0.02, "{}", integral);
+    }
+
+    #[test]
+    fn test_pdfs() {
+        let cosine = CosinePdf::new(Vec3::new(0.0, 1.0, 0.0));
+        assert_normalized(&SpherePdf);
+        assert_normalized(&cosine);
+        assert_normalized(&MixturePdf::new(&SpherePdf, &cosine));
+        let reflected = Vec3::new(0.0, 0.6, 0.8);
+        assert_normalized(&FuzzPdf::new(reflected, 0.5));
+        assert_normalized(&FuzzPdf::new(reflected, 1.5));
+
+        let mut rng = seeded_rng(0, 1);
+        for _ in 0..100 {
+            let direction = cosine.generate(&mut rng);
+            assert!(direction.y() >= 0.0);
+            assert!(cosine.value(direction) >= 0.0);
+        }
+        assert_eq!(cosine.value(Vec3::new(0.0, -1.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_hittable_pdf() {
+        let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let sphere = Sphere::new(Point3::new(0.0, 0.0, -2.0), 1.0, mat.clone());
+        let quad = Quad::new(
+            Point3::new(-1.0, -1.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            mat,
+        );
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        assert_normalized(&HittablePdf::new(&sphere, origin, 0.0));
+        assert_normalized(&HittablePdf::new(&quad, origin, 0.0));
+        assert_normalized(&HittablePdf::new(&sphere, Point3::new(0.0, 0.5, -2.0), 0.0));
+        let transformed = Transform::new(
+            Quad::new(
+                Point3::new(-1.0, -1.0, 1.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(0.0, 2.0, 0.0),
+                Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            ),
+            Mat4::rotation_y(40.0) * Mat4::scaling(Vec3::new(2.0, 0.5, 1.0)),
+        );
+        assert_normalized(&HittablePdf::new(&transformed, origin, 0.0));
+
+        // Generated directions point at the object.
+        let mut rng = seeded_rng(0, 2);
+        let pdf = HittablePdf::new(&sphere, origin, 0.0);
+        for _ in 0..100 {
+            assert!(pdf.value(pdf.generate(&mut rng)) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::rtweekend::Rng;
+use crate::vec3::{Point3, Vec3};
+use rand::seq::SliceRandom;
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise on a lattice of random unit vectors, smoothed with a Hermite cubic.
+pub struct Perlin {
+    randvec: [Vec3; POINT_COUNT],
+    perm_x: [usize; POINT_COUNT],
+    perm_y: [usize; POINT_COUNT],
+    perm_z: [usize; POINT_COUNT],
+}
+
+impl Perlin {
+    pub fn new(rng: &mut Rng) -> Self {
+        let mut randvec = [Vec3::default(); POINT_COUNT];
+        for v in randvec.iter_mut() {
+            *v = Vec3::random_bounded(rng, -1.0, 1.0).unit();
+        }
+
+        Perlin {
+            randvec,
+            perm_x: Perlin::generate_perm(rng),
+            perm_y: Perlin::generate_perm(rng),
+            perm_z: Perlin::generate_perm(rng),
+        }
+    }
+
+    pub fn noise(&self, p: Point3) -> f64 {
+        // Returns a value in about [-1,1], continuous in p.
+        let u = p.x() - f64::floor(p.x());
+        let v = p.y() - f64::floor(p.y());
+        let w = p.z() - f64::floor(p.z());
+
+        let i = f64::floor(p.x()) as i64;
+        let j = f64::floor(p.y()) as i64;
+        let k = f64::floor(p.z()) as i64;
+
+        let mut c = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.randvec[self.perm_x[((i + di as i64) & 255) as usize]
+                        ^ self.perm_y[((j + dj as i64) & 255) as usize]
+                        ^ self.perm_z[((k + dk as i64) & 255) as usize]];
+                }
+            }
+        }
+
+        perlin_interp(&c, u, v, w)
+    }
+
+    pub fn turb(&self, p: Point3, depth: i32) -> f64 {
+        // Sum of `depth` octaves of noise, each at twice the frequency and half the weight.
+        let mut accum = 0.0;
+        let mut temp_p = p;
+        let mut weight = 1.0;
+
+        for _ in 0..depth {
+            accum += weight * self.noise(temp_p);
+            weight *= 0.5;
+            temp_p = temp_p * 2.0;
+        }
+
+        f64::abs(accum)
+    }
+
+    fn generate_perm(rng: &mut Rng) -> [usize; POINT_COUNT] {
+        let mut p = [0; POINT_COUNT];
+        for (i, v) in p.iter_mut().enumerate() {
+            *v = i;
+        }
+        p.shuffle(rng);
+        p
+    }
+}
+
+fn perlin_interp(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    // Hermite smoothing of the fractional coordinates removes grid artifacts.
+    let uu = u * u * (3.0 - 2.0 * u);
+    let vv = v * v * (3.0 - 2.0 * v);
+    let ww = w * w * (3.0 - 2.0 * w);
+    let mut accum = 0.0;
+
+    for (i, plane) in c.iter().enumerate() {
+        for (j, row) in plane.iter().enumerate() {
+            for (k, corner) in row.iter().enumerate() {
+                let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                let weight_v = Vec3::new(u - fi, v - fj, w - fk);
+                accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                    * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                    * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                    * corner.dot(weight_v);
+            }
+        }
+    }
+
+    accum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtweekend::seeded_rng;
+
+    #[test]
+    fn test_noise_range() {
+        let perlin = Perlin::new(&mut seeded_rng(0, 0));
+        for i in 0..1000 {
+            let t = i as f64 * 0.137;
+            let p = Point3::new(t, 0.5 * t, -0.25 * t);
+            let n = perlin.noise(p);
+            assert!((-1.0..=1.0).contains(&n), "{}", n);
+            assert!(perlin.turb(p, 7) >= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_noise_is_zero_on_lattice() {
+        // Gradient noise vanishes at integer lattice points.
+        let perlin = Perlin::new(&mut seeded_rng(0, 0));
+        assert_eq!(perlin.noise(Point3::new(3.0, -2.0, 7.0)), 0.0);
+    }
+
+    #[test]
+    fn test_noise_is_seeded() {
+        let p = Point3::new(0.3, 1.7, -2.2);
+        let a = Perlin::new(&mut seeded_rng(1, 0)).noise(p);
+        let b = Perlin::new(&mut seeded_rng(1, 0)).noise(p);
+        let c = Perlin::new(&mut seeded_rng(2, 0)).noise(p);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::{self, Interval};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+// Plane through `q` spanned by the edges `u` and `v`, shared by the planar primitives.
+#[derive(Copy, Clone)]
+pub(crate) struct PlaneBasis {
+    pub q: Point3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub normal: Vec3, // Unit normal, in the direction of u x v
+    w: Vec3,
+    d: f64,
+}
+
+impl PlaneBasis {
+    pub fn new(q: Point3, u: Vec3, v: Vec3) -> Self {
+        let n = u.cross(v);
+        let normal = n.unit();
+        PlaneBasis {
+            q,
+            u,
+            v,
+            normal,
+            w: n / n.dot(n),
+            d: normal.dot(q),
+        }
+    }
+
+    pub fn from_normal(q: Point3, normal: Vec3) -> Self {
+        // Spans the plane with two perpendicular unit edges.
+        let normal = normal.unit();
+        let a = if f64::abs(normal.x()) > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = normal.cross(a).unit();
+        let u = v.cross(normal);
+        PlaneBasis::new(q, u, v)
+    }
+
+    pub fn hit(&self, r: Ray, ray_t: Interval) -> Option<(f64, f64, f64)> {
+        // Returns the ray parameter t and the planar coordinates (alpha, beta) of the hit point,
+        // so that it lies at q + alpha * u + beta * v.
+
+        // No hit if the ray is parallel to the plane.
+        let denom = self.normal.dot(r.direction());
+        if f64::abs(denom) < 1e-8 {
+            return None;
+        }
+
+        // Return None if the hit point parameter t is outside the ray interval.
+        let t = (self.d - self.normal.dot(r.origin())) / denom;
+        if !ray_t.contains(t) {
+            return None;
+        }
+
+        let planar_hitpt_vector = r.at(t) - self.q;
+        let alpha = self.w.dot(planar_hitpt_vector.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar_hitpt_vector));
+
+        Some((t, alpha, beta))
+    }
+}
+
+// Infinite plane through `point`, with surface coordinates repeating every unit of length.
+pub struct Plane<M: Material> {
+    basis: PlaneBasis,
+    pub mat: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Plane<M> {
+    pub fn new(point: Point3, normal: Vec3, mat: M) -> Self {
+        let basis = PlaneBasis::from_normal(point, normal);
+
+        // Only a plane perpendicular to an axis has finite extent, along that axis.
+        let n = basis.normal;
+        let extent = |axis: usize| {
+            if n[(axis + 1) % 3] == 0.0 && n[(axis + 2) % 3] == 0.0 {
+                Interval::new(point[axis], point[axis])
+            } else {
+                interval::UNIVERSE
+            }
+        };
+
+        Plane {
+            basis,
+            mat,
+            bbox: Aabb::new(extent(0), extent(1), extent(2)),
+        }
+    }
+}
+
+impl<M: Material> Hittable for Plane<M> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        let (t, alpha, beta) = self.basis.hit(r, ray_t)?;
+
+        let mut rec = HitRecord {
+            t,
+            p: r.at(t),
+            normal: self.basis.normal,
+            u: alpha - f64::floor(alpha),
+            v: beta - f64::floor(beta),
+            front_face: false,
+            material: &self.mat,
+        };
+        rec.set_face_normal(r, self.basis.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        visit(&self.mat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn test_plane() {
+        let plane = Plane::new(
+            Point3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Lambertian::new(Vec3::new(0.5, 0.5, 0.5)),
+        );
+
+        let down = Ray::new(Point3::new(3.7, 2.0, -8.2), Vec3::new(0.0, -1.0, 0.0));
+        let rec = plane.hit(down, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 3.0);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal.y(), 1.0);
+        assert!((0.0..1.0).contains(&rec.u) && (0.0..1.0).contains(&rec.v));
+
+        let up = Ray::new(Point3::new(0.0, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = plane.hit(up, interval::UNIVERSE).unwrap();
+        assert!(!rec.front_face);
+        assert_eq!(rec.normal.y(), -1.0);
+
+        let parallel = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(plane.hit(parallel, interval::UNIVERSE).is_none());
+
+        let bbox = plane.bounding_box();
+        assert!(bbox.y.size() < 0.001);
+        assert_eq!(bbox.x.size(), f64::INFINITY);
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::plane::PlaneBasis;
+use crate::ray::Ray;
+use crate::rtweekend::{random_f64, Rng};
+use crate::vec3::{Point3, Vec3};
+
+// Parallelogram with corner `q` and edges `u` and `v`; the front face is on the u x v side.
+pub struct Quad<M: Material> {
+    basis: PlaneBasis,
+    pub mat: M,
+    bbox: Aabb,
+    area: f64,
+}
+
+impl<M: Material> Quad<M> {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: M) -> Self {
+        // Compute the bounding box of all four vertices.
+        let bbox_diagonal1 = Aabb::from_points(q, q + u + v);
+        let bbox_diagonal2 = Aabb::from_points(q + u, q + v);
+
+        Quad {
+            basis: PlaneBasis::new(q, u, v),
+            mat,
+            bbox: Aabb::surrounding(bbox_diagonal1, bbox_diagonal2),
+            area: u.cross(v).length(),
+        }
+    }
+}
+
+impl<M: Material> Hittable for Quad<M> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        let (t, alpha, beta) = self.basis.hit(r, ray_t)?;
+
+        // Determine if the hit point lies within the planar shape using its plane coordinates.
+        let unit_interval = Interval::new(0.0, 1.0);
+        if !unit_interval.contains(alpha) || !unit_interval.contains(beta) {
+            return None;
+        }
+
+        let mut rec = HitRecord {
+            t,
+            p: r.at(t),
+            normal: self.basis.normal,
+            u: alpha,
+            v: beta,
+            front_face: false,
+            material: &self.mat,
+        };
+        rec.set_face_normal(r, self.basis.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        visit(&self.mat)
+    }
+
+    // Samples points uniformly over the area, converted to a density over solid angle.
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        let Some(rec) = self.hit(
+            Ray::with_time(origin, direction, time),
+            Interval::new(0.001, f64::INFINITY),
+        ) else {
+            return 0.0;
+        };
+
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(direction.dot(rec.normal) / direction.length());
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: Point3, _time: f64, rng: &mut Rng) -> Vec3 {
+        let p = self.basis.q + (random_f64(rng) * self.basis.u) + (random_f64(rng) * self.basis.v);
+        p - origin
+    }
+}
+
+// Returns the 3D box (six sides) that contains the two opposite vertices a & b.
+pub fn make_box(a: Point3, b: Point3, mat: Arc<dyn Material>) -> HittableList {
+    let mut sides = HittableList::new();
+
+    let min = Point3::new(
+        f64::min(a.x(), b.x()),
+        f64::min(a.y(), b.y()),
+        f64::min(a.z(), b.z()),
+    );
+    let max = Point3::new(
+        f64::max(a.x(), b.x()),
+        f64::max(a.y(), b.y()),
+        f64::max(a.z(), b.z()),
+    );
+
+    let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+    let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+    let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+    let front = Point3::new(min.x(), min.y(), max.z());
+    let right = Point3::new(max.x(), min.y(), max.z());
+    let back = Point3::new(max.x(), min.y(), min.z());
+    let left = Point3::new(min.x(), min.y(), min.z());
+    let top = Point3::new(min.x(), max.y(), max.z());
+    let bottom = Point3::new(min.x(), min.y(), min.z());
+
+    sides.add(Quad::new(front, dx, dy, mat.clone()));
+    sides.add(Quad::new(right, -dz, dy, mat.clone()));
+    sides.add(Quad::new(back, -dx, dy, mat.clone()));
+    sides.add(Quad::new(left, dz, dy, mat.clone()));
+    sides.add(Quad::new(top, dx, -dz, mat.clone()));
+    sides.add(Quad::new(bottom, dx, dz, mat));
+
+    sides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::interval;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn test_quad() {
+        let quad = Quad::new(
+            Point3::new(-1.0, -1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+
+        let r = Ray::new(Point3::new(0.5, 2.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = quad.hit(r, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 5.0);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal.z(), 1.0);
+        assert_eq!((rec.u, rec.v), (0.75, 0.75));
+
+        let behind = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = quad.hit(behind, interval::UNIVERSE).unwrap();
+        assert!(!rec.front_face);
+        assert_eq!(rec.normal.z(), -1.0);
+
+        let outside = Ray::new(Point3::new(1.5, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(quad.hit(outside, interval::UNIVERSE).is_none());
+    }
+
+    #[test]
+    fn test_make_box() {
+        let mat: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let sides = make_box(Point3::new(1.0, 2.0, 3.0), Point3::new(0.0, 0.0, 0.0), mat);
+
+        let bbox = sides.bounding_box();
+        assert!(f64::abs(bbox.x.max - 1.0) < 0.001);
+        assert!(f64::abs(bbox.y.max - 2.0) < 0.001);
+        assert!(f64::abs(bbox.z.min) < 0.001);
+
+        // Every side faces outwards.
+        let center = Point3::new(0.5, 1.0, 1.5);
+        for dir in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ] {
+            let r = Ray::new(center + 10.0 * dir, -dir);
+            let rec = sides.hit(r, interval::UNIVERSE).unwrap();
+            assert!(rec.front_face, "{:?}", dir);
+            assert_eq!(rec.normal.dot(dir), 1.0);
+        }
+
+        assert_eq!(sides.into_objects().len(), 6);
+    }
+}
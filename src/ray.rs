@@ -1,4 +1,3 @@
-
 use crate::vec3::Point3;
 use crate::vec3::Vec3;
 
@@ -6,11 +5,28 @@ use crate::vec3::Vec3;
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    tm: f64,
+    seed: u64, // Drawn from the path's generator for objects that sample along the ray
 }
 
 impl Ray {
     pub fn new(orig: Point3, dir: Vec3) -> Ray {
-        Ray { orig, dir }
+        Ray::with_time(orig, dir, 0.0)
+    }
+
+    pub fn with_time(orig: Point3, dir: Vec3, tm: f64) -> Ray {
+        Ray {
+            orig,
+            dir,
+            tm,
+            seed: 0,
+        }
+    }
+
+    // Participating media derive their free-flight distances from the seed, which the
+    // integrator draws for every traced segment.
+    pub fn with_seed(self, seed: u64) -> Ray {
+        Ray { seed, ..self }
     }
 
     pub fn at(self, t: f64) -> Point3 {
@@ -25,4 +41,12 @@ impl Ray {
     pub fn origin(self) -> Point3 {
         self.orig
     }
+
+    pub fn time(self) -> f64 {
+        self.tm
+    }
+
+    pub fn seed(self) -> u64 {
+        self.seed
+    }
 }
@@ -1,18 +1,79 @@
 use core::f64;
-use rand::prelude::*;
+use rand::{Rng as _, SeedableRng};
+
+// Random number generator threaded through ray generation and scattering. A given seed always
+// produces the same sequence, so renders are reproducible.
+pub type Rng = rand_pcg::Pcg64Mcg;
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * f64::consts::PI / 180.0
 }
 
-pub fn random_f64() -> f64 {
-    let mut rng = rand::thread_rng();
-    // Returns a random real in [0,1s).
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+pub fn seeded_rng(seed: u64, stream: u64) -> Rng {
+    // Returns a generator for one of many independent streams (e.g. one per pixel) derived from
+    // a global seed.
+    Rng::seed_from_u64(splitmix64(seed ^ splitmix64(stream)))
+}
+
+pub fn random_f64(rng: &mut Rng) -> f64 {
+    // Returns a random real in [0,1).
     rng.gen::<f64>()
 }
 
-pub fn random_f64_bounded(min: f64, max: f64) -> f64 {
-    let mut rng = rand::thread_rng();
+pub fn random_f64_bounded(rng: &mut Rng, min: f64, max: f64) -> f64 {
     // Returns a random real in [min,max).
     min + (max - min) * rng.gen::<f64>()
 }
+
+pub fn hashed_f64(values: &[f64]) -> f64 {
+    // Returns a pseudo-random real in [0,1) determined by `values`, for code that has no
+    // generator at hand but must stay reproducible.
+    let hash = values
+        .iter()
+        .fold(0, |hash, value| splitmix64(hash ^ value.to_bits()));
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_f64() {
+        let x = hashed_f64(&[1.0, 2.0, 3.0]);
+        assert!((0.0..1.0).contains(&x));
+        assert_eq!(x, hashed_f64(&[1.0, 2.0, 3.0]));
+        assert_ne!(x, hashed_f64(&[1.0, 2.0, 3.5]));
+        assert_ne!(x, hashed_f64(&[3.0, 2.0, 1.0]));
+    }
+
+    #[test]
+    fn test_seeded_rng() {
+        let a: Vec<f64> = (0..4).map(|_| random_f64(&mut seeded_rng(42, 7))).collect();
+        assert!(a.iter().all(|x| *x == a[0]));
+
+        let mut rng = seeded_rng(42, 7);
+        let mut other = seeded_rng(42, 8);
+        assert_ne!(random_f64(&mut rng), random_f64(&mut other));
+
+        let mut rng = seeded_rng(42, 7);
+        let mut other = seeded_rng(43, 7);
+        assert_ne!(random_f64(&mut rng), random_f64(&mut other));
+    }
+
+    #[test]
+    fn test_random_bounded() {
+        let mut rng = seeded_rng(0, 0);
+        for _ in 0..100 {
+            let x = random_f64_bounded(&mut rng, -2.0, 3.0);
+            assert!((-2.0..3.0).contains(&x));
+        }
+    }
+}
@@ -0,0 +1,620 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::camera::{AdaptiveSampling, Camera, Fog};
+use crate::color::Color;
+use crate::constant_medium::ConstantMedium;
+use crate::hittable_list::HittableList;
+use crate::integrator::IntegratorKind;
+use crate::material::{Dielectric, DiffuseLight, Isotropic, Lambertian, Metal};
+use crate::perlin::Perlin;
+use crate::quad::{make_box, Quad};
+use crate::rtweekend::{random_f64, random_f64_bounded, seeded_rng};
+use crate::sphere::Sphere;
+use crate::texture::{CheckerTexture, MarbleTexture};
+use crate::transform::{RotateY, Translate};
+use crate::vec3::{Point3, Vec3};
+
+// Arguments of `Camera::new` and its rendering options, kept around so they can be tweaked
+// before the camera is built.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraSettings {
+    pub image_width: i32,
+    pub aspect_ratio: f64,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub roulette_depth: i32,
+    pub vfov: i32,
+    pub defocus_angle: f64,
+    pub focus_dist: f64,
+    pub lookfrom: Point3,
+    pub lookat: Point3,
+    pub vup: Vec3,
+    pub background: Option<Color>,
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+    pub fog: Option<Fog>,
+    pub integrator: IntegratorKind,
+    pub adaptive: Option<AdaptiveSampling>,
+}
+
+impl CameraSettings {
+    pub fn build(&self) -> Camera {
+        let mut camera = Camera::new(
+            self.image_width,
+            self.aspect_ratio,
+            self.samples_per_pixel,
+            self.max_depth,
+            self.vfov,
+            self.defocus_angle,
+            self.focus_dist,
+            self.lookfrom,
+            self.lookat,
+            self.vup,
+        );
+        camera.roulette_depth = self.roulette_depth;
+        camera.background = self.background;
+        camera.shutter_open = self.shutter_open;
+        camera.shutter_close = self.shutter_close;
+        camera.fog = self.fog;
+        camera.integrator = self.integrator.build();
+        camera.adaptive = self.adaptive;
+        camera
+    }
+
+    // Returns the key of the first setting out of its range, and what is wrong with it.
+    pub fn check(&self) -> Result<(), (&'static str, &'static str)> {
+        let at = |key, result: Result<(), &'static str>| result.map_err(|message| (key, message));
+        at("image_width", check_at_least_one(self.image_width))?;
+        at("aspect_ratio", check_positive(self.aspect_ratio))?;
+        at(
+            "samples_per_pixel",
+            check_at_least_one(self.samples_per_pixel),
+        )?;
+        at("max_depth", check_at_least_one(self.max_depth))?;
+        at("roulette_depth", check_not_negative(self.roulette_depth))?;
+        at("vfov", check_vfov(self.vfov))?;
+        at("defocus_angle", check_defocus_angle(self.defocus_angle))?;
+        at("focus_dist", check_positive(self.focus_dist))?;
+        at("shutter_open", check_shutter_time(self.shutter_open))?;
+        at("shutter_close", check_shutter_time(self.shutter_close))?;
+        if self.shutter_close < self.shutter_open {
+            return Err(("shutter_close", "must not be before shutter_open"));
+        }
+        if let Some(adaptive) = self.adaptive {
+            at("adaptive.threshold", check_positive(adaptive.threshold))?;
+            at(
+                "adaptive.min_samples",
+                check_at_least_one(adaptive.min_samples),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            image_width: 400,
+            aspect_ratio: 16.0 / 9.0,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            roulette_depth: 5,
+            vfov: 90,
+            defocus_angle: 0.0,
+            focus_dist: 10.0,
+            lookfrom: Point3::new(0.0, 0.0, 0.0),
+            lookat: Point3::new(0.0, 0.0, -1.0),
+            vup: Vec3::new(0.0, 1.0, 0.0),
+            background: None,
+            shutter_open: 0.0,
+            shutter_close: 1.0,
+            fog: None,
+            integrator: IntegratorKind::default(),
+            adaptive: None,
+        }
+    }
+}
+
+// Valid ranges of camera settings, shared by scene files and command-line options. Each check
+// returns what is wrong with a value out of its range.
+pub fn check_at_least_one(value: i32) -> Result<(), &'static str> {
+    if value < 1 {
+        return Err("must be at least 1");
+    }
+    Ok(())
+}
+
+pub fn check_not_negative(value: i32) -> Result<(), &'static str> {
+    if value < 0 {
+        return Err("must not be negative");
+    }
+    Ok(())
+}
+
+pub fn check_positive(value: f64) -> Result<(), &'static str> {
+    if !value.is_finite() || value <= 0.0 {
+        return Err("must be a positive number");
+    }
+    Ok(())
+}
+
+// A field of view of 0 or 180 degrees or more has no usable viewport.
+pub fn check_vfov(value: i32) -> Result<(), &'static str> {
+    if !(1..180).contains(&value) {
+        return Err("must be between 1 and 179 degrees");
+    }
+    Ok(())
+}
+
+// Objects move from time 0 to time 1, so the shutter must open and close within that span.
+pub fn check_shutter_time(value: f64) -> Result<(), &'static str> {
+    if !(0.0..=1.0).contains(&value) {
+        return Err("must be between 0 and 1");
+    }
+    Ok(())
+}
+
+// Rays through a pixel spread at most half a turn around its center.
+pub fn check_defocus_angle(value: f64) -> Result<(), &'static str> {
+    if !(0.0..180.0).contains(&value) {
+        return Err("must be at least 0 and below 180 degrees");
+    }
+    Ok(())
+}
+
+pub struct Scene {
+    pub world: HittableList,
+    pub camera: CameraSettings,
+    pub lights: HittableList, // Emitters that are also in `world`, sampled directly
+}
+
+impl Scene {
+    pub fn new(world: HittableList, camera: CameraSettings) -> Self {
+        Scene {
+            world,
+            camera,
+            lights: HittableList::new(),
+        }
+    }
+}
+
+pub const BUILTIN_SCENES: [&str; 9] = [
+    "book1-final",
+    "bouncing-spheres",
+    "three-spheres",
+    "checkered-spheres",
+    "perlin-spheres",
+    "simple-light",
+    "quads",
+    "cornell-box",
+    "cornell-smoke",
+];
+
+// Scenes with a random layout derive it from `seed`.
+pub fn builtin(name: &str, seed: u64) -> Option<Scene> {
+    match name {
+        "book1-final" => Some(book1_final(seed)),
+        "bouncing-spheres" => Some(bouncing_spheres(seed)),
+        "three-spheres" => Some(three_spheres()),
+        "checkered-spheres" => Some(checkered_spheres()),
+        "perlin-spheres" => Some(perlin_spheres(seed)),
+        "simple-light" => Some(simple_light(seed)),
+        "quads" => Some(quads()),
+        "cornell-box" => Some(cornell_box()),
+        "cornell-smoke" => Some(cornell_smoke()),
+        _ => None,
+    }
+}
+
+// Final render of "Ray Tracing in One Weekend": a field of small random spheres around three
+// large ones.
+pub fn book1_final(seed: u64) -> Scene {
+    let material_ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    let world = random_spheres(seed, material_ground, false);
+
+    let camera = CameraSettings {
+        image_width: 1200,
+        samples_per_pixel: 500,
+        vfov: 20,
+        defocus_angle: 0.6,
+        focus_dist: 10.0,
+        lookfrom: Point3::new(13.0, 2.0, 3.0),
+        lookat: Point3::new(0.0, 0.0, 0.0),
+        ..CameraSettings::default()
+    };
+
+    Scene::new(world, camera)
+}
+
+// Start of "Ray Tracing: The Next Week": the same field on a checkered ground, with the diffuse
+// spheres bouncing up while the shutter is open.
+pub fn bouncing_spheres(seed: u64) -> Scene {
+    let checker = Arc::new(CheckerTexture::from_colors(
+        0.32,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let world = random_spheres(seed, Lambertian::from_texture(checker), true);
+
+    let camera = CameraSettings {
+        vfov: 20,
+        defocus_angle: 0.6,
+        focus_dist: 10.0,
+        lookfrom: Point3::new(13.0, 2.0, 3.0),
+        lookat: Point3::new(0.0, 0.0, 0.0),
+        ..CameraSettings::default()
+    };
+
+    Scene::new(world, camera)
+}
+
+fn random_spheres(seed: u64, material_ground: Lambertian, bouncing: bool) -> HittableList {
+    let mut rng = seeded_rng(seed, 0);
+    let mut world = HittableList::new();
+
+    world.add(Sphere::<Lambertian>::new(
+        Point3::new(0., -1000., 0.),
+        1000.0,
+        material_ground,
+    ));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = random_f64(&mut rng);
+            let center = Point3::new(
+                a as f64 + 0.9 * random_f64(&mut rng),
+                0.2,
+                b as f64 + 0.9 * random_f64(&mut rng),
+            );
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                if choose_mat < 0.8 {
+                    let albedo = Color::random(&mut rng) * Color::random(&mut rng);
+
+                    let sphere_material = Lambertian::new(albedo);
+                    if bouncing {
+                        let center2 =
+                            center + Vec3::new(0.0, random_f64_bounded(&mut rng, 0.0, 0.5), 0.0);
+                        world.add(Sphere::moving(center, center2, 0.2, sphere_material));
+                    } else {
+                        world.add(Sphere::new(center, 0.2, sphere_material));
+                    }
+                } else if choose_mat < 0.95 {
+                    let albedo = Color::random(&mut rng);
+                    let fuzz = random_f64_bounded(&mut rng, 0.0, 0.5);
+                    let sphere_material = Metal::new(albedo, fuzz);
+                    world.add(Sphere::new(center, 0.2, sphere_material));
+                } else {
+                    let sphere_material = Dielectric::new(1.5);
+                    world.add(Sphere::new(center, 0.2, sphere_material));
+                }
+            }
+        }
+    }
+
+    let material1 = Dielectric::new(1.5);
+    world.add(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1));
+
+    let material2 = Lambertian::new(Color::new(0.4, 0.2, 0.1));
+    world.add(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2));
+
+    let material3 = Metal::new(Color::new(0.7, 0.6, 0.5), 0.0);
+    world.add(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3));
+
+    world
+}
+
+// Ground, a diffuse sphere between a hollow glass sphere and a metal one.
+pub fn three_spheres() -> Scene {
+    let mut world = HittableList::new();
+
+    let material_ground = Lambertian::new(Color::new(0.8, 0.8, 0.0));
+    let material_center = Lambertian::new(Color::new(0.1, 0.2, 0.5));
+    let material_left = Dielectric::new(1.5);
+    let material_bubble = Dielectric::new(1.0 / 1.5);
+    let material_right = Metal::new(Color::new(0.8, 0.6, 0.2), 1.0);
+
+    world.add(Sphere::new(
+        Point3::new(0.0, -100.5, -1.0),
+        100.0,
+        material_ground,
+    ));
+    world.add(Sphere::new(
+        Point3::new(0.0, 0.0, -1.2),
+        0.5,
+        material_center,
+    ));
+    world.add(Sphere::new(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.5,
+        material_left,
+    ));
+    world.add(Sphere::new(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.4,
+        material_bubble,
+    ));
+    world.add(Sphere::new(
+        Point3::new(1.0, 0.0, -1.0),
+        0.5,
+        material_right,
+    ));
+
+    let camera = CameraSettings {
+        vfov: 20,
+        lookfrom: Point3::new(-2.0, 2.0, 1.0),
+        lookat: Point3::new(0.0, 0.0, -1.0),
+        defocus_angle: 10.0,
+        focus_dist: 3.4,
+        ..CameraSettings::default()
+    };
+
+    Scene::new(world, camera)
+}
+
+// Two large spheres sharing a 3D checker texture.
+pub fn checkered_spheres() -> Scene {
+    let mut world = HittableList::new();
+
+    let checker = Arc::new(CheckerTexture::from_colors(
+        0.32,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+
+    world.add(Sphere::new(
+        Point3::new(0.0, -10.0, 0.0),
+        10.0,
+        Lambertian::from_texture(checker.clone()),
+    ));
+    world.add(Sphere::new(
+        Point3::new(0.0, 10.0, 0.0),
+        10.0,
+        Lambertian::from_texture(checker),
+    ));
+
+    let camera = CameraSettings {
+        vfov: 20,
+        lookfrom: Point3::new(13.0, 2.0, 3.0),
+        lookat: Point3::new(0.0, 0.0, 0.0),
+        ..CameraSettings::default()
+    };
+
+    Scene::new(world, camera)
+}
+
+// A marble sphere resting on a marble ground; the noise lattice is derived from `seed`.
+pub fn perlin_spheres(seed: u64) -> Scene {
+    let mut world = HittableList::new();
+
+    let marble = Arc::new(MarbleTexture::new(
+        Perlin::new(&mut seeded_rng(seed, 0)),
+        4.0,
+        Color::new(1.0, 1.0, 1.0),
+    ));
+
+    world.add(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Lambertian::from_texture(marble.clone()),
+    ));
+    world.add(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        Lambertian::from_texture(marble),
+    ));
+
+    let camera = CameraSettings {
+        vfov: 20,
+        lookfrom: Point3::new(13.0, 2.0, 3.0),
+        lookat: Point3::new(0.0, 0.0, 0.0),
+        ..CameraSettings::default()
+    };
+
+    Scene::new(world, camera)
+}
+
+// The marble spheres in the dark, lit by a glowing sphere above them.
+pub fn simple_light(seed: u64) -> Scene {
+    let mut scene = perlin_spheres(seed);
+
+    let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+    let lamps = [
+        Arc::new(Sphere::new(Point3::new(0.0, 7.0, 0.0), 2.0, light.clone())),
+        Arc::new(Sphere::new(Point3::new(4.0, 1.5, -3.0), 1.0, light)),
+    ];
+    for lamp in lamps {
+        scene.world.add(lamp.clone());
+        scene.lights.add(lamp);
+    }
+
+    scene.camera = CameraSettings {
+        vfov: 20,
+        lookfrom: Point3::new(26.0, 3.0, 6.0),
+        lookat: Point3::new(0.0, 2.0, 0.0),
+        background: Some(Color::new(0.0, 0.0, 0.0)),
+        integrator: IntegratorKind::Mis,
+        ..CameraSettings::default()
+    };
+
+    scene
+}
+
+// Five colored quads facing the camera from the sides of a cube.
+pub fn quads() -> Scene {
+    let mut world = HittableList::new();
+
+    let left_red = Lambertian::new(Color::new(1.0, 0.2, 0.2));
+    let back_green = Lambertian::new(Color::new(0.2, 1.0, 0.2));
+    let right_blue = Lambertian::new(Color::new(0.2, 0.2, 1.0));
+    let upper_orange = Lambertian::new(Color::new(1.0, 0.5, 0.0));
+    let lower_teal = Lambertian::new(Color::new(0.2, 0.8, 0.8));
+
+    world.add(Quad::new(
+        Point3::new(-3.0, -2.0, 5.0),
+        Vec3::new(0.0, 0.0, -4.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        left_red,
+    ));
+    world.add(Quad::new(
+        Point3::new(-2.0, -2.0, 0.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        back_green,
+    ));
+    world.add(Quad::new(
+        Point3::new(3.0, -2.0, 1.0),
+        Vec3::new(0.0, 0.0, 4.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        right_blue,
+    ));
+    world.add(Quad::new(
+        Point3::new(-2.0, 3.0, 1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 4.0),
+        upper_orange,
+    ));
+    world.add(Quad::new(
+        Point3::new(-2.0, -3.0, 5.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -4.0),
+        lower_teal,
+    ));
+
+    let camera = CameraSettings {
+        aspect_ratio: 1.0,
+        vfov: 80,
+        lookfrom: Point3::new(0.0, 0.0, 9.0),
+        lookat: Point3::new(0.0, 0.0, 0.0),
+        ..CameraSettings::default()
+    };
+
+    Scene::new(world, camera)
+}
+
+// The Cornell box: a white room with a red and a green wall, lit through a hole in the ceiling.
+pub fn cornell_box() -> Scene {
+    let mut world = HittableList::new();
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    cornell_walls(&mut world, white.clone());
+
+    let light = Arc::new(Quad::new(
+        Point3::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+        DiffuseLight::new(Color::new(15.0, 15.0, 15.0)),
+    ));
+    world.add(light.clone());
+
+    world.add(cornell_tall_box(white.clone()));
+    world.add(cornell_short_box(white));
+
+    let mut scene = Scene::new(world, cornell_camera());
+    scene.lights.add(light);
+    scene
+}
+
+// The Cornell box with its two blocks made of black and white smoke, under a larger light.
+pub fn cornell_smoke() -> Scene {
+    let mut world = HittableList::new();
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    cornell_walls(&mut world, white.clone());
+
+    let light = Arc::new(Quad::new(
+        Point3::new(113.0, 554.0, 127.0),
+        Vec3::new(330.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 305.0),
+        DiffuseLight::new(Color::new(7.0, 7.0, 7.0)),
+    ));
+    world.add(light.clone());
+
+    world.add(ConstantMedium::new(
+        cornell_tall_box(white.clone()),
+        0.01,
+        Isotropic::new(Color::new(0.0, 0.0, 0.0)),
+    ));
+    world.add(ConstantMedium::new(
+        cornell_short_box(white),
+        0.01,
+        Isotropic::new(Color::new(1.0, 1.0, 1.0)),
+    ));
+
+    let mut scene = Scene::new(world, cornell_camera());
+    scene.lights.add(light);
+    scene
+}
+
+fn cornell_walls(world: &mut HittableList, white: Arc<Lambertian>) {
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+
+    world.add(Quad::new(
+        Point3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        green,
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        red,
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    ));
+    world.add(Quad::new(
+        Point3::new(555.0, 555.0, 555.0),
+        Vec3::new(-555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -555.0),
+        white.clone(),
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        white,
+    ));
+}
+
+fn cornell_tall_box(mat: Arc<Lambertian>) -> Translate<RotateY<HittableList>> {
+    let box1 = make_box(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        mat,
+    );
+    let box1 = RotateY::new(box1, 15.0);
+    Translate::new(box1, Vec3::new(265.0, 0.0, 295.0))
+}
+
+fn cornell_short_box(mat: Arc<Lambertian>) -> Translate<RotateY<HittableList>> {
+    let box2 = make_box(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        mat,
+    );
+    let box2 = RotateY::new(box2, -18.0);
+    Translate::new(box2, Vec3::new(130.0, 0.0, 65.0))
+}
+
+fn cornell_camera() -> CameraSettings {
+    CameraSettings {
+        aspect_ratio: 1.0,
+        image_width: 600,
+        samples_per_pixel: 200,
+        vfov: 40,
+        lookfrom: Point3::new(278.0, 278.0, -800.0),
+        lookat: Point3::new(278.0, 278.0, 0.0),
+        background: Some(Color::new(0.0, 0.0, 0.0)),
+        integrator: IntegratorKind::Mis,
+        ..CameraSettings::default()
+    }
+}
@@ -0,0 +1,909 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use toml_edit::ImDocument;
+
+use crate::color::Color;
+use crate::disk::Disk;
+use crate::hittable::Hittable;
+use crate::hittable_list::HittableList;
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::matrix::Mat4;
+use crate::mesh::{MeshMaterials, TriangleMesh};
+use crate::perlin::Perlin;
+use crate::plane::Plane;
+use crate::quad::{make_box, Quad};
+use crate::rtweekend::seeded_rng;
+use crate::scene::{CameraSettings, Scene};
+use crate::sphere::Sphere;
+use crate::texture::{
+    CheckerTexture, ImageTexture, MarbleTexture, NoiseTexture, SolidColor, Texture,
+    TurbulenceTexture, WoodTexture,
+};
+use crate::transform::Transform;
+use crate::triangle::Triangle;
+use crate::vec3::{Point3, Vec3};
+
+// Declarative scene description, read from TOML:
+//
+//     [camera]
+//     vfov = 20
+//     lookfrom = [-2.0, 2.0, 1.0]
+//
+//     [materials.ground]
+//     type = "lambertian"
+//     albedo = { type = "checker", scale = 0.32, even = [0.2, 0.3, 0.1], odd = [0.9, 0.9, 0.9] }
+//
+//     [[objects]]
+//     type = "sphere"
+//     center = [0.0, -100.5, -1.0]
+//     radius = 100.0
+//     material = "ground"
+//
+// Camera keys match the arguments of `Camera::new` and default to `CameraSettings::default()`;
+// `background = [r, g, b]` replaces the sky gradient, e.g. with black for scenes lit by lights,
+// `roulette_depth` is the number of bounces before dim paths may be ended at random,
+// `shutter_open`/`shutter_close` bound the times sampled for motion blur, within the [0, 1] span of
+// object motion, and
+// `fog = { density = 0.1, color = [0.7, 0.7, 0.8] }` fades distant objects. `integrator` is
+// one of `integrator::INTEGRATORS`, e.g. "mis" for path tracing with light sampling or "normals"
+// to check the geometry. `adaptive = { threshold = 0.05, min_samples = 16 }` lets pixels stop
+// sampling before `samples_per_pixel` once their relative error is below the threshold.
+// Albedos are either a color or a texture table (checker, image, noise, turbulence, marble or
+// wood); image paths are relative to the scene file. Spheres and quads made of a `diffuse_light`
+// material are also sampled directly as lights.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneFile {
+    #[serde(default)]
+    camera: CameraSettings,
+    // Sorted, so materials are created in the same order on every run.
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialSpec>,
+    #[serde(default)]
+    objects: Vec<ObjectSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum MaterialSpec {
+    Lambertian {
+        albedo: AlbedoSpec,
+    },
+    Metal {
+        albedo: AlbedoSpec,
+        fuzz: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+    },
+    #[serde(rename = "diffuse_light")]
+    DiffuseLight {
+        emit: AlbedoSpec,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AlbedoSpec {
+    Color(Color),
+    Texture(TextureSpec),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum TextureSpec {
+    Checker {
+        scale: f64,
+        even: Box<AlbedoSpec>,
+        odd: Box<AlbedoSpec>,
+    },
+    Image {
+        path: PathBuf,
+    },
+    // Perlin textures default to seed 0, so a scene file always renders the same pattern.
+    Noise {
+        scale: f64,
+        seed: Option<u64>,
+    },
+    Turbulence {
+        scale: f64,
+        depth: Option<i32>,
+        seed: Option<u64>,
+    },
+    Marble {
+        scale: f64,
+        albedo: Option<Color>,
+        seed: Option<u64>,
+    },
+    Wood {
+        scale: f64,
+        light: Color,
+        dark: Color,
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum ObjectSpec {
+    // A moving sphere goes from `center` at time 0 to `center2` at time 1.
+    Sphere {
+        center: Point3,
+        center2: Option<Point3>,
+        radius: f64,
+        material: String,
+    },
+    Quad {
+        q: Point3,
+        u: Vec3,
+        v: Vec3,
+        material: String,
+    },
+    Triangle {
+        a: Point3,
+        b: Point3,
+        c: Point3,
+        normals: Option<[Vec3; 3]>,
+        uvs: Option<[[f64; 2]; 3]>,
+        material: String,
+    },
+    Disk {
+        center: Point3,
+        normal: Vec3,
+        radius: f64,
+        material: String,
+    },
+    Plane {
+        point: Point3,
+        normal: Vec3,
+        material: String,
+    },
+    // Triangle mesh read from an OBJ, PLY or STL file, with optional materials for some of its
+    // groups. PLY vertex colors, if enabled, replace the material with a blended Lambertian.
+    Mesh {
+        path: PathBuf,
+        material: String,
+        #[serde(default)]
+        groups: HashMap<String, String>,
+        #[serde(default)]
+        vertex_colors: bool,
+        transform: Option<TransformSpec>,
+    },
+    // Axis-aligned box between two opposite corners.
+    #[serde(rename = "box")]
+    Cuboid {
+        a: Point3,
+        b: Point3,
+        material: String,
+        transform: Option<TransformSpec>,
+    },
+}
+
+// Placement of an object: scaled, then rotated around x, y and z (in degrees), then translated.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransformSpec {
+    scale: Option<ScaleSpec>,
+    rotate: Option<Vec3>,
+    translate: Option<Vec3>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScaleSpec {
+    Uniform(f64),
+    Axes(Vec3),
+}
+
+impl TransformSpec {
+    fn matrix(&self) -> Mat4 {
+        let scale = match self.scale {
+            Some(ScaleSpec::Uniform(factor)) => Vec3::new(factor, factor, factor),
+            Some(ScaleSpec::Axes(factors)) => factors,
+            None => Vec3::new(1.0, 1.0, 1.0),
+        };
+        let rotate = self.rotate.unwrap_or_default();
+        let translate = self.translate.unwrap_or_default();
+
+        Mat4::translation(translate)
+            * Mat4::rotation_z(rotate.z())
+            * Mat4::rotation_y(rotate.y())
+            * Mat4::rotation_x(rotate.x())
+            * Mat4::scaling(scale)
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(PathBuf, io::Error),
+    // Malformed TOML or a key with the wrong type; the message points at the offending line.
+    Parse(Option<PathBuf>, toml::de::Error),
+    // An object refers to a material name missing from the `materials` table.
+    UnknownMaterial {
+        path: Option<PathBuf>,
+        line: usize,
+        key: String,
+        name: String,
+    },
+    // A value that parses but cannot be used, such as a transform scaling by zero.
+    InvalidValue {
+        path: Option<PathBuf>,
+        line: usize,
+        key: String,
+        message: String,
+    },
+}
+
+impl SceneError {
+    fn with_path(self, path: &Path) -> Self {
+        // Names the scene file in errors raised while parsing its contents.
+        let path = Some(path.to_path_buf());
+        match self {
+            SceneError::Parse(None, err) => SceneError::Parse(path, err),
+            SceneError::UnknownMaterial {
+                path: None,
+                line,
+                key,
+                name,
+            } => SceneError::UnknownMaterial {
+                path,
+                line,
+                key,
+                name,
+            },
+            SceneError::InvalidValue {
+                path: None,
+                line,
+                key,
+                message,
+            } => SceneError::InvalidValue {
+                path,
+                line,
+                key,
+                message,
+            },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            SceneError::Parse(Some(path), err) => write!(f, "{}: {}", path.display(), err),
+            SceneError::Parse(None, err) => write!(f, "{}", err),
+            SceneError::UnknownMaterial {
+                path,
+                line,
+                key,
+                name,
+            } => {
+                if let Some(path) = path {
+                    write!(f, "{}: ", path.display())?;
+                }
+                write!(f, "line {}, {}: unknown material '{}'", line, key, name)
+            }
+            SceneError::InvalidValue {
+                path,
+                line,
+                key,
+                message,
+            } => {
+                if let Some(path) = path {
+                    write!(f, "{}: ", path.display())?;
+                }
+                write!(f, "line {}, {}: {}", line, key, message)
+            }
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+pub fn load(path: &Path) -> Result<Scene, SceneError> {
+    let src = fs::read_to_string(path).map_err(|e| SceneError::Io(path.to_path_buf(), e))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    parse_relative_to(&src, base_dir).map_err(|e| e.with_path(path))
+}
+
+pub fn parse(src: &str) -> Result<Scene, SceneError> {
+    parse_relative_to(src, Path::new(""))
+}
+
+// A scene file's syntax tree, which keeps where each of its keys is; serde deserializes most
+// tables without spans.
+struct KeySpans<'a> {
+    src: &'a str,
+    doc: ImDocument<&'a str>,
+}
+
+impl KeySpans<'_> {
+    // Line of the value at a dotted path of keys, with array elements keyed by their index, e.g.
+    // "materials.floor.albedo.scale" or "objects.2.transform". A key missing from the file, such
+    // as a setting left at its default, points at the innermost table around it that is there.
+    fn line(&self, path: &str) -> usize {
+        let mut item = self.doc.as_item();
+        let mut offset = 0;
+        for key in path.split('.') {
+            let next = item
+                .get(key)
+                .or_else(|| key.parse::<usize>().ok().and_then(|index| item.get(index)));
+            let Some(next) = next else {
+                break;
+            };
+            // Tables made by dotted keys or nested headers have no span of their own.
+            if let Some(span) = next.span() {
+                offset = span.start;
+            }
+            item = next;
+        }
+        self.src[..offset].matches('\n').count() + 1
+    }
+}
+
+fn check_camera(camera: &CameraSettings, spans: &KeySpans) -> Result<(), SceneError> {
+    camera.check().map_err(|(key, message)| {
+        let key = format!("camera.{}", key);
+        SceneError::InvalidValue {
+            path: None,
+            line: spans.line(&key),
+            key,
+            message: message.to_string(),
+        }
+    })
+}
+
+fn add_transformed<H: Hittable + 'static>(
+    world: &mut HittableList,
+    object: H,
+    matrix: Option<Mat4>,
+) {
+    match matrix {
+        Some(matrix) => world.add(Transform::new(object, matrix)),
+        None => world.add(object),
+    }
+}
+
+fn texture(
+    spec: AlbedoSpec,
+    base_dir: &Path,
+    key: &str,
+    line_of: &dyn Fn(&str) -> usize,
+) -> Result<Arc<dyn Texture>, SceneError> {
+    Ok(match spec {
+        AlbedoSpec::Color(albedo) => Arc::new(SolidColor::new(albedo)),
+        AlbedoSpec::Texture(TextureSpec::Checker { scale, even, odd }) => {
+            if !scale.is_finite() || scale <= 0.0 {
+                let key = format!("{}.scale", key);
+                return Err(SceneError::InvalidValue {
+                    path: None,
+                    line: line_of(&key),
+                    key,
+                    message: "the checker scale must be positive".to_string(),
+                });
+            }
+            Arc::new(CheckerTexture::new(
+                scale,
+                texture(*even, base_dir, &format!("{}.even", key), line_of)?,
+                texture(*odd, base_dir, &format!("{}.odd", key), line_of)?,
+            ))
+        }
+        AlbedoSpec::Texture(TextureSpec::Image { path }) => {
+            let path = base_dir.join(path);
+            Arc::new(ImageTexture::load(&path).map_err(|e| SceneError::Io(path, e))?)
+        }
+        AlbedoSpec::Texture(TextureSpec::Noise { scale, seed }) => {
+            Arc::new(NoiseTexture::new(perlin(seed), scale))
+        }
+        AlbedoSpec::Texture(TextureSpec::Turbulence { scale, depth, seed }) => Arc::new(
+            TurbulenceTexture::new(perlin(seed), scale, depth.unwrap_or(7)),
+        ),
+        AlbedoSpec::Texture(TextureSpec::Marble {
+            scale,
+            albedo,
+            seed,
+        }) => Arc::new(MarbleTexture::new(
+            perlin(seed),
+            scale,
+            albedo.unwrap_or(Color::new(1.0, 1.0, 1.0)),
+        )),
+        AlbedoSpec::Texture(TextureSpec::Wood {
+            scale,
+            light,
+            dark,
+            seed,
+        }) => Arc::new(WoodTexture::new(perlin(seed), scale, light, dark)),
+    })
+}
+
+fn perlin(seed: Option<u64>) -> Perlin {
+    Perlin::new(&mut seeded_rng(seed.unwrap_or(0), 0))
+}
+
+fn material(
+    spec: MaterialSpec,
+    base_dir: &Path,
+    name: &str,
+    line_of: &dyn Fn(&str) -> usize,
+) -> Result<Arc<dyn Material>, SceneError> {
+    let albedo_key = format!("materials.{}.albedo", name);
+    Ok(match spec {
+        MaterialSpec::Lambertian { albedo } => Arc::new(Lambertian::from_texture(texture(
+            albedo,
+            base_dir,
+            &albedo_key,
+            line_of,
+        )?)),
+        MaterialSpec::Metal { albedo, fuzz } => Arc::new(Metal::from_texture(
+            texture(albedo, base_dir, &albedo_key, line_of)?,
+            fuzz,
+        )),
+        MaterialSpec::Dielectric { refraction_index } => {
+            Arc::new(Dielectric::new(refraction_index))
+        }
+        MaterialSpec::DiffuseLight { emit } => {
+            let key = format!("materials.{}.emit", name);
+            Arc::new(DiffuseLight::from_texture(texture(
+                emit, base_dir, &key, line_of,
+            )?))
+        }
+    })
+}
+
+fn parse_relative_to(src: &str, base_dir: &Path) -> Result<Scene, SceneError> {
+    let file: SceneFile = toml::from_str(src).map_err(|e| SceneError::Parse(None, e))?;
+    let spans = KeySpans {
+        src,
+        // `toml` parses with the same parser, so it has already reported any syntax error.
+        doc: ImDocument::parse(src).expect("scene file already parsed"),
+    };
+    let line_of = |key: &str| spans.line(key);
+    check_camera(&file.camera, &spans)?;
+
+    // Spheres and quads made of these are sampled as lights.
+    let light_materials: HashSet<String> = file
+        .materials
+        .iter()
+        .filter(|(_, spec)| matches!(spec, MaterialSpec::DiffuseLight { .. }))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let materials = file
+        .materials
+        .into_iter()
+        .map(|(name, spec)| {
+            let material = material(spec, base_dir, &name, &line_of)?;
+            Ok((name, material))
+        })
+        .collect::<Result<HashMap<String, Arc<dyn Material>>, SceneError>>()?;
+
+    let mut world = HittableList::new();
+    let mut lights = HittableList::new();
+    for (index, object) in file.objects.into_iter().enumerate() {
+        // Point errors at the object's key, or at the object itself.
+        let line_of = |key: &str| line_of(&format!("objects.{}.{}", index, key));
+        let line = line_of("material");
+        let material = |name: &str| {
+            materials
+                .get(name)
+                .cloned()
+                .ok_or_else(|| SceneError::UnknownMaterial {
+                    path: None,
+                    line,
+                    key: format!("objects[{}].material", index),
+                    name: name.to_string(),
+                })
+        };
+
+        let matrix = |transform: Option<TransformSpec>| {
+            let Some(transform) = transform else {
+                return Ok(None);
+            };
+            let matrix = transform.matrix();
+            if matrix.inverse().is_none() {
+                return Err(SceneError::InvalidValue {
+                    path: None,
+                    line: line_of("transform"),
+                    key: format!("objects[{}].transform", index),
+                    message: "the transform is not invertible".to_string(),
+                });
+            }
+            Ok(Some(matrix))
+        };
+
+        match object {
+            ObjectSpec::Sphere {
+                center,
+                center2,
+                radius,
+                material: name,
+            } => {
+                let sphere = Arc::new(Sphere::moving(
+                    center,
+                    center2.unwrap_or(center),
+                    radius,
+                    material(&name)?,
+                ));
+                if light_materials.contains(&name) {
+                    lights.add(sphere.clone());
+                }
+                world.add(sphere)
+            }
+            ObjectSpec::Quad {
+                q,
+                u,
+                v,
+                material: name,
+            } => {
+                let quad = Arc::new(Quad::new(q, u, v, material(&name)?));
+                if light_materials.contains(&name) {
+                    lights.add(quad.clone());
+                }
+                world.add(quad)
+            }
+            ObjectSpec::Triangle {
+                a,
+                b,
+                c,
+                normals,
+                uvs,
+                material: name,
+            } => {
+                let mut triangle = Triangle::new(a, b, c, material(&name)?);
+                if let Some(normals) = normals {
+                    triangle = triangle.with_normals(normals);
+                }
+                if let Some(uvs) = uvs {
+                    triangle = triangle.with_uvs(uvs.map(|[u, v]| (u, v)));
+                }
+                world.add(triangle)
+            }
+            ObjectSpec::Disk {
+                center,
+                normal,
+                radius,
+                material: name,
+            } => world.add(Disk::new(center, normal, radius, material(&name)?)),
+            ObjectSpec::Plane {
+                point,
+                normal,
+                material: name,
+            } => world.add(Plane::new(point, normal, material(&name)?)),
+            ObjectSpec::Mesh {
+                path,
+                material: name,
+                groups,
+                vertex_colors,
+                transform,
+            } => {
+                let mut mesh_materials = MeshMaterials::new(material(&name)?);
+                if vertex_colors {
+                    mesh_materials = mesh_materials.with_vertex_colors();
+                }
+                for (group, name) in groups {
+                    let material = materials.get(&name).cloned().ok_or_else(|| {
+                        SceneError::UnknownMaterial {
+                            path: None,
+                            line: line_of(&format!("groups.{}", group)),
+                            key: format!("objects[{}].groups.{}", index, group),
+                            name,
+                        }
+                    })?;
+                    mesh_materials = mesh_materials.with_group(&group, material);
+                }
+                let path = base_dir.join(path);
+                let mesh = TriangleMesh::load(&path, &mesh_materials)
+                    .map_err(|e| SceneError::Io(path, e))?;
+                add_transformed(&mut world, mesh, matrix(transform)?)
+            }
+            ObjectSpec::Cuboid {
+                a,
+                b,
+                material: name,
+                transform,
+            } => add_transformed(
+                &mut world,
+                make_box(a, b, material(&name)?),
+                matrix(transform)?,
+            ),
+        }
+    }
+
+    Ok(Scene {
+        world,
+        camera: file.camera,
+        lights,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let scene = parse(
+            r#"
+            [camera]
+            vfov = 20
+            lookfrom = [-2.0, 2.0, 1.0]
+            background = [0, 0, 0]
+
+            [camera.fog]
+            density = 0.05
+            color = [0.7, 0.7, 0.8]
+
+            [materials.ground]
+            type = "lambertian"
+            albedo = [0.8, 0.8, 0.0]
+
+            [materials.gold]
+            type = "metal"
+            albedo = [0.8, 0.6, 0.2]
+            fuzz = 0.3
+
+            [materials.checker]
+            type = "lambertian"
+            albedo = { type = "checker", scale = 0.5, even = [0, 0, 0], odd = [1, 1, 1] }
+
+            [materials.light]
+            type = "diffuse_light"
+            emit = [4, 4, 4]
+
+            [materials.marble]
+            type = "lambertian"
+            albedo = { type = "marble", scale = 4.0, seed = 3 }
+
+            [materials.wood]
+            type = "lambertian"
+            albedo = { type = "wood", scale = 8.0, light = [0.8, 0.6, 0.4], dark = [0.4, 0.2, 0.1] }
+
+            [[objects]]
+            type = "sphere"
+            center = [0.0, -100.5, -1.0]
+            radius = 100.0
+            material = "ground"
+
+            [[objects]]
+            type = "sphere"
+            center = [1.0, 0.0, -1.0]
+            center2 = [1.0, 0.5, -1.0]
+            radius = 0.5
+            material = "gold"
+
+            [[objects]]
+            type = "quad"
+            q = [-1, 2, -1]
+            u = [2, 0, 0]
+            v = [0, 0, 2]
+            material = "light"
+
+            [[objects]]
+            type = "triangle"
+            a = [0, 0, 0]
+            b = [1, 0, 0]
+            c = [0, 1, 0]
+            uvs = [[0, 0], [1, 0], [0, 1]]
+            material = "wood"
+
+            [[objects]]
+            type = "disk"
+            center = [0, 3, 0]
+            normal = [0, -1, 0]
+            radius = 0.5
+            material = "light"
+
+            [[objects]]
+            type = "plane"
+            point = [0, 0, -10]
+            normal = [0, 0, 1]
+            material = "marble"
+
+            [[objects]]
+            type = "box"
+            a = [0, 0, 0]
+            b = [1, 1, 1]
+            material = "checker"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(scene.camera.vfov, 20);
+        assert_eq!(scene.camera.lookfrom.x(), -2.0);
+        assert_eq!(scene.camera.background.unwrap().length(), 0.0);
+        assert_eq!(scene.camera.fog.unwrap().density, 0.05);
+        assert_eq!(
+            scene.camera.image_width,
+            CameraSettings::default().image_width
+        );
+        assert_eq!(scene.lights.into_objects().len(), 1);
+        assert_eq!(scene.world.into_objects().len(), 7);
+    }
+
+    #[test]
+    fn test_unknown_material() {
+        let err = parse(
+            r#"
+            [[objects]]
+            type = "sphere"
+            center = [0.0, 0.0, -1.0]
+            radius = 0.5
+            material = "missing"
+            "#,
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "line 6, objects[0].material: unknown material 'missing'"
+        );
+    }
+
+    #[test]
+    fn test_transform() {
+        let src = r#"
+            [materials.grey]
+            type = "lambertian"
+            albedo = [0.5, 0.5, 0.5]
+
+            [[objects]]
+            type = "box"
+            a = [0.0, 0.0, 0.0]
+            b = [1.0, 1.0, 1.0]
+            material = "grey"
+            transform = { scale = 2.0, rotate = [0.0, 90.0, 0.0], translate = [0.0, 0.0, -5.0] }
+            "#;
+        let scene = parse(src).unwrap();
+        let bbox = scene.world.bounding_box();
+        assert!(bbox.x.contains(0.0) && bbox.x.contains(2.0) && bbox.x.size() < 2.001);
+        assert!(bbox.z.contains(-5.0) && bbox.z.contains(-7.0) && bbox.z.size() < 2.001);
+
+        let err = parse(&src.replace("scale = 2.0", "scale = [1.0, 0.0, 1.0]"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 11, objects[0].transform: the transform is not invertible"
+        );
+    }
+
+    #[test]
+    fn test_missing_image() {
+        let err = parse(
+            r#"
+            [materials.earth]
+            type = "lambertian"
+            albedo = { type = "image", path = "missing.png" }
+            "#,
+        )
+        .err()
+        .unwrap();
+
+        assert!(err.to_string().starts_with("missing.png: "), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_error_line() {
+        let err = parse(
+            r#"
+            [camera]
+            vfov = "wide"
+            "#,
+        )
+        .err()
+        .unwrap();
+
+        let message = err.to_string();
+        assert!(message.contains("line 3"), "{}", message);
+        assert!(message.contains("vfov"), "{}", message);
+    }
+
+    #[test]
+    fn test_camera_ranges() {
+        let src = r#"
+            [camera]
+            vfov = 20
+            image_width = 0
+            "#;
+        let err = parse(src).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4, camera.image_width: must be at least 1"
+        );
+
+        let src = src.replace("image_width = 0", "samples_per_pixel = -3");
+        let err = parse(&src).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4, camera.samples_per_pixel: must be at least 1"
+        );
+
+        let src = src.replace("samples_per_pixel = -3", "shutter_close = 1.5");
+        let err = parse(&src).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4, camera.shutter_close: must be between 0 and 1"
+        );
+
+        let src = src.replace(
+            "shutter_close = 1.5",
+            "shutter_open = 0.5\nshutter_close = 0.25",
+        );
+        let err = parse(&src).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 5, camera.shutter_close: must not be before shutter_open"
+        );
+
+        for (setting, message) in [
+            ("aspect_ratio = 0.0", "must be a positive number"),
+            ("vfov = 180", "must be between 1 and 179 degrees"),
+            (
+                "defocus_angle = -1.0",
+                "must be at least 0 and below 180 degrees",
+            ),
+            ("focus_dist = 0.0", "must be a positive number"),
+            ("roulette_depth = -1", "must not be negative"),
+        ] {
+            // A comment mentioning the key does not move the error.
+            let src = format!("# {}\n[camera]\n{}\n", setting, setting);
+            let key = setting.split(' ').next().unwrap();
+            let err = parse(&src).err().unwrap();
+            assert_eq!(
+                err.to_string(),
+                format!("line 3, camera.{}: {}", key, message)
+            );
+        }
+
+        let src = "[camera]\nadaptive = { min_samples = 4, threshold = -1.0 }\n";
+        let err = parse(src).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 2, camera.adaptive.threshold: must be a positive number"
+        );
+    }
+
+    #[test]
+    fn test_checker_scale() {
+        let err = parse(
+            r#"
+            [materials.floor]
+            type = "lambertian"
+            albedo = { type = "checker", scale = 0.0, even = [0, 0, 0], odd = [1, 1, 1] }
+            "#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4, materials.floor.albedo.scale: the checker scale must be positive"
+        );
+
+        // Dotted keys make tables without spans of their own.
+        let err = parse(
+            r#"
+            [materials]
+            floor.type = "lambertian"
+            floor.albedo.type = "checker"
+            floor.albedo.even = [0, 0, 0]
+            floor.albedo.odd = [1, 1, 1]
+            floor.albedo.scale = -1.0
+            "#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 7, materials.floor.albedo.scale: the checker scale must be positive"
+        );
+    }
+}
@@ -1,28 +1,60 @@
+use crate::aabb::Aabb;
 use crate::hittable::HitRecord;
 use crate::interval::Interval;
 use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::rtweekend::{random_f64, Rng};
 use crate::vec3;
 use crate::{hittable::Hittable, vec3::Point3};
+use core::f64;
 
 pub struct Sphere<M: Material> {
-    center: Point3,
+    center: Ray, // Center at time 0, moving by the direction per unit of time
     radius: f64,
     pub mat: M,
+    bbox: Aabb,
 }
 
 impl<M: Material> Sphere<M> {
+    // Stationary Sphere
     pub fn new(center: Point3, radius: f64, mat: M) -> Self {
+        Sphere::moving(center, center, radius, mat)
+    }
+
+    // Moving Sphere, at center1 at time 0 and center2 at time 1
+    pub fn moving(center1: Point3, center2: Point3, radius: f64, mat: M) -> Self {
+        let radius = f64::max(0.0, radius);
+        let rvec = vec3::Vec3::new(radius, radius, radius);
+        let box1 = Aabb::from_points(center1 - rvec, center1 + rvec);
+        let box2 = Aabb::from_points(center2 - rvec, center2 + rvec);
         Sphere {
-            center,
-            radius: f64::max(0.0, radius),
+            center: Ray::new(center1, center2 - center1),
+            radius,
             mat,
+            bbox: Aabb::surrounding(box1, box2),
         }
     }
 }
 
+fn get_sphere_uv(p: Point3) -> (f64, f64) {
+    // p: a given point on the sphere of radius one, centered at the origin.
+    // u: returned value [0,1] of angle around the Y axis from X=-1.
+    // v: returned value [0,1] of angle from Y=-1 to Y=+1.
+    //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
+    //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
+    //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
+
+    let theta = f64::acos(-p.y());
+    let phi = f64::atan2(-p.z(), p.x()) + f64::consts::PI;
+
+    (phi / (2.0 * f64::consts::PI), theta / f64::consts::PI)
+}
+
 impl<M: Material> Hittable for Sphere<M> {
-    fn hit(&self, r: crate::ray::Ray, ray_t: Interval) -> Option<HitRecord> {
-        let oc = self.center - r.origin();
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        let current_center = self.center.at(r.time());
+        let oc = current_center - r.origin();
         let a = r.direction().length_squared();
 
         let h = r.direction().dot(oc);
@@ -50,13 +82,153 @@ impl<M: Material> Hittable for Sphere<M> {
             t: root,
             p: r.at(root),
             normal: vec3::Vec3::new(0.0, 0.0, 0.0),
+            u: 0.0,
+            v: 0.0,
             front_face: false,
             material: &self.mat,
         };
 
-        let outward_normal = (rec.p - self.center) / self.radius;
+        let outward_normal = (rec.p - current_center) / self.radius;
         rec.set_face_normal(r, outward_normal);
+        (rec.u, rec.v) = get_sphere_uv(outward_normal);
 
         Some(rec)
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        visit(&self.mat)
+    }
+
+    // Samples the cone of directions subtended by the sphere, where it is at `time`. From inside
+    // the sphere every direction hits it, so they are sampled uniformly.
+    fn pdf_value(&self, origin: Point3, direction: vec3::Vec3, time: f64) -> f64 {
+        if self
+            .hit(
+                Ray::with_time(origin, direction, time),
+                Interval::new(0.001, f64::INFINITY),
+            )
+            .is_none()
+        {
+            return 0.0;
+        }
+
+        let dist_squared = (self.center.at(time) - origin).length_squared();
+        if dist_squared <= self.radius * self.radius {
+            return 1.0 / (4.0 * f64::consts::PI);
+        }
+        let cos_theta_max = f64::sqrt(1.0 - self.radius * self.radius / dist_squared);
+        let solid_angle = 2.0 * f64::consts::PI * (1.0 - cos_theta_max);
+
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> vec3::Vec3 {
+        let direction = self.center.at(time) - origin;
+        let distance_squared = direction.length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return vec3::Vec3::random_unit(rng);
+        }
+        let uvw = Onb::new(direction);
+        uvw.transform(random_to_sphere(self.radius, distance_squared, rng))
+    }
+}
+
+fn random_to_sphere(radius: f64, distance_squared: f64, rng: &mut Rng) -> vec3::Vec3 {
+    // Returns a direction around +z within the cone of a sphere seen from `distance_squared`.
+    let r1 = random_f64(rng);
+    let r2 = random_f64(rng);
+    let z = 1.0 + r2 * (f64::sqrt(f64::max(0.0, 1.0 - radius * radius / distance_squared)) - 1.0);
+
+    let phi = 2.0 * f64::consts::PI * r1;
+    let x = f64::cos(phi) * f64::sqrt(1.0 - z * z);
+    let y = f64::sin(phi) * f64::sqrt(1.0 - z * z);
+
+    vec3::Vec3::new(x, y, z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::interval::UNIVERSE;
+    use crate::material::Lambertian;
+    use crate::rtweekend::seeded_rng;
+
+    #[test]
+    fn test_sphere_uv() {
+        let cases = [
+            (Point3::new(1.0, 0.0, 0.0), (0.5, 0.5)),
+            (Point3::new(-1.0, 0.0, 0.0), (0.0, 0.5)),
+            (Point3::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point3::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (Point3::new(0.0, 0.0, 1.0), (0.25, 0.5)),
+            (Point3::new(0.0, 0.0, -1.0), (0.75, 0.5)),
+        ];
+        for (p, (u, v)) in cases {
+            let (actual_u, actual_v) = get_sphere_uv(p);
+            assert!(f64::abs(actual_u - u) < 1e-9, "{:?}", p);
+            assert!(f64::abs(actual_v - v) < 1e-9, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_moving_sphere() {
+        let mat = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::moving(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            1.0,
+            mat,
+        );
+
+        // The bounding box covers the whole path.
+        let bbox = sphere.bounding_box();
+        assert_eq!((bbox.x.min, bbox.x.max), (-1.0, 3.0));
+
+        let d = vec3::Vec3::new(0.0, 0.0, -1.0);
+        let r = Ray::with_time(Point3::new(2.0, 0.0, 5.0), d, 0.0);
+        assert!(sphere.hit(r, UNIVERSE).is_none());
+        let r = Ray::with_time(Point3::new(2.0, 0.0, 5.0), d, 1.0);
+        let rec = sphere.hit(r, UNIVERSE).unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_eq!(rec.normal.z(), 1.0);
+
+        let r = Ray::with_time(Point3::new(1.0, 0.0, 5.0), d, 0.5);
+        assert_eq!(sphere.hit(r, UNIVERSE).unwrap().t, 4.0);
+
+        // Light samples aim at the sphere where it is at the sample time.
+        let mut rng = seeded_rng(0, 0);
+        let origin = Point3::new(2.0, 0.0, 5.0);
+        for _ in 0..100 {
+            let direction = sphere.random(origin, 1.0, &mut rng);
+            assert!(sphere.pdf_value(origin, direction, 1.0) > 0.0);
+            assert_eq!(sphere.pdf_value(origin, direction, 0.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_sample_from_inside() {
+        // Seen from inside, the sphere covers every direction.
+        let sphere = Sphere::new(
+            Point3::new(0.0, 0.0, 0.0),
+            2.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let mut rng = seeded_rng(0, 0);
+        let origin = Point3::new(0.5, 1.0, 0.0);
+        let mut sum = vec3::Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..1000 {
+            let direction = sphere.random(origin, 0.0, &mut rng);
+            assert!(f64::abs(direction.length() - 1.0) < 1e-9);
+            let pdf = sphere.pdf_value(origin, direction, 0.0);
+            assert!(f64::abs(pdf - 1.0 / (4.0 * f64::consts::PI)) < 1e-12);
+            sum += direction;
+        }
+        // Not biased towards the center.
+        assert!(sum.length() < 100.0, "{:?}", sum);
+    }
 }
@@ -0,0 +1,319 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::color::{self, Color};
+use crate::image::Image;
+use crate::interval::Interval;
+use crate::perlin::Perlin;
+use crate::vec3::Point3;
+
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+}
+
+pub struct SolidColor {
+    albedo: Color,
+}
+impl SolidColor {
+    pub fn new(albedo: Color) -> Self {
+        SolidColor { albedo }
+    }
+}
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        self.albedo
+    }
+}
+
+// Alternates between two textures on a 3D grid of cubes with sides of length `scale`.
+pub struct CheckerTexture {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+impl CheckerTexture {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        CheckerTexture {
+            inv_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, c1: Color, c2: Color) -> Self {
+        CheckerTexture::new(
+            scale,
+            Arc::new(SolidColor::new(c1)),
+            Arc::new(SolidColor::new(c2)),
+        )
+    }
+}
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        let x_integer = f64::floor(self.inv_scale * p.x()) as i64;
+        let y_integer = f64::floor(self.inv_scale * p.y()) as i64;
+        let z_integer = f64::floor(self.inv_scale * p.z()) as i64;
+
+        let is_even = (x_integer + y_integer + z_integer) % 2 == 0;
+
+        if is_even {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+// Maps an image over the [0,1]x[0,1] surface coordinates, with v pointing up.
+pub struct ImageTexture {
+    image: Image,
+}
+impl ImageTexture {
+    pub fn new(image: Image) -> Self {
+        ImageTexture { image }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        // Reads a PNG, JPEG or PPM file, undoing the gamma applied by `color::linear_to_gamma`.
+        let decoded = ::image::open(path)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            .into_rgb8();
+
+        let mut image = Image::new(decoded.width() as usize, decoded.height() as usize);
+        for (x, y, pixel) in decoded.enumerate_pixels() {
+            let [r, g, b] = pixel.0;
+            image.set(
+                x as usize,
+                y as usize,
+                Color::new(
+                    color::gamma_to_linear(r as f64 / 255.0),
+                    color::gamma_to_linear(g as f64 / 255.0),
+                    color::gamma_to_linear(b as f64 / 255.0),
+                ),
+            );
+        }
+        Ok(ImageTexture { image })
+    }
+}
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        // If we have no texture data, then return solid cyan as a debugging aid.
+        if self.image.width() == 0 || self.image.height() == 0 {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
+        // Clamp input texture coordinates to [0,1] x [1,0]
+        let u = Interval::new(0.0, 1.0).clamp(u);
+        let v = 1.0 - Interval::new(0.0, 1.0).clamp(v); // Flip V to image coordinates
+
+        let i = usize::min(
+            (u * self.image.width() as f64) as usize,
+            self.image.width() - 1,
+        );
+        let j = usize::min(
+            (v * self.image.height() as f64) as usize,
+            self.image.height() - 1,
+        );
+
+        self.image.get(i, j)
+    }
+}
+
+// Blends the colors given at the corners a, b and c of a triangle. u and v are the barycentric
+// weights of b and c, as reported by `Triangle::hit` for triangles without surface coordinates,
+// so the blend follows the triangle through transforms.
+#[derive(Clone)]
+pub struct VertexColorTexture {
+    colors: [Color; 3],
+}
+impl VertexColorTexture {
+    pub fn new(colors: [Color; 3]) -> Self {
+        VertexColorTexture { colors }
+    }
+}
+impl Texture for VertexColorTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        (1.0 - u - v) * self.colors[0] + u * self.colors[1] + v * self.colors[2]
+    }
+}
+
+// Smooth gray Perlin noise, with features about 1/scale apart.
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+}
+impl NoiseTexture {
+    pub fn new(noise: Perlin, scale: f64) -> Self {
+        NoiseTexture { noise, scale }
+    }
+}
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        Color::new(1.0, 1.0, 1.0) * 0.5 * (1.0 + self.noise.noise(self.scale * p))
+    }
+}
+
+// Gray turbulence, i.e. `depth` octaves of Perlin noise.
+pub struct TurbulenceTexture {
+    noise: Perlin,
+    scale: f64,
+    depth: i32,
+}
+impl TurbulenceTexture {
+    pub fn new(noise: Perlin, scale: f64, depth: i32) -> Self {
+        TurbulenceTexture {
+            noise,
+            scale,
+            depth,
+        }
+    }
+}
+impl Texture for TurbulenceTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        Color::new(1.0, 1.0, 1.0) * self.noise.turb(self.scale * p, self.depth)
+    }
+}
+
+// Marble veins: a sine wave along z whose phase is shifted by turbulence.
+pub struct MarbleTexture {
+    noise: Perlin,
+    scale: f64,
+    albedo: Color,
+}
+impl MarbleTexture {
+    pub fn new(noise: Perlin, scale: f64, albedo: Color) -> Self {
+        MarbleTexture {
+            noise,
+            scale,
+            albedo,
+        }
+    }
+}
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        self.albedo * 0.5 * (1.0 + f64::sin(self.scale * p.z() + 10.0 * self.noise.turb(p, 7)))
+    }
+}
+
+// Wood grain: rings around the y axis, `scale` rings per unit, distorted by turbulence.
+pub struct WoodTexture {
+    noise: Perlin,
+    scale: f64,
+    light: Color,
+    dark: Color,
+}
+impl WoodTexture {
+    pub fn new(noise: Perlin, scale: f64, light: Color, dark: Color) -> Self {
+        WoodTexture {
+            noise,
+            scale,
+            light,
+            dark,
+        }
+    }
+}
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let radius = f64::sqrt(p.x() * p.x() + p.z() * p.z());
+        let rings = self.scale * radius + 2.0 * self.noise.turb(p, 4);
+        let t = rings - f64::floor(rings);
+        (1.0 - t) * self.light + t * self.dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::{self, PngBitDepth, PngEncoder};
+    use crate::rtweekend::seeded_rng;
+
+    #[test]
+    fn test_checker() {
+        let checker =
+            CheckerTexture::from_colors(1.0, Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(checker.value(0.0, 0.0, Point3::new(0.5, 0.5, 0.5)).x(), 1.0);
+        assert_eq!(checker.value(0.0, 0.0, Point3::new(1.5, 0.5, 0.5)).x(), 0.0);
+        assert_eq!(
+            checker.value(0.0, 0.0, Point3::new(-0.5, 0.5, 0.5)).x(),
+            0.0
+        );
+        assert_eq!(
+            checker.value(0.0, 0.0, Point3::new(-0.5, -0.5, 0.5)).x(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_noise_textures() {
+        let light = Color::new(0.9, 0.7, 0.5);
+        let dark = Color::new(0.4, 0.2, 0.1);
+        let textures: Vec<Box<dyn Texture>> = vec![
+            Box::new(NoiseTexture::new(Perlin::new(&mut seeded_rng(0, 0)), 4.0)),
+            Box::new(TurbulenceTexture::new(
+                Perlin::new(&mut seeded_rng(0, 0)),
+                4.0,
+                7,
+            )),
+            Box::new(MarbleTexture::new(
+                Perlin::new(&mut seeded_rng(0, 0)),
+                4.0,
+                light,
+            )),
+            Box::new(WoodTexture::new(
+                Perlin::new(&mut seeded_rng(0, 0)),
+                4.0,
+                light,
+                dark,
+            )),
+        ];
+
+        for texture in textures {
+            for i in 0..100 {
+                let t = i as f64 * 0.173;
+                let c = texture.value(0.0, 0.0, Point3::new(t, -t, 0.5 * t));
+                for channel in 0..3 {
+                    assert!(c[channel] >= 0.0, "{:?}", c);
+                    assert!(c[channel] <= 2.0, "{:?}", c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_image() {
+        let mut image = Image::new(2, 2);
+        image.set(0, 0, Color::new(1.0, 0.0, 0.0)); // top left
+        image.set(1, 1, Color::new(0.0, 1.0, 0.0)); // bottom right
+        let texture = ImageTexture::new(image);
+        let p = Point3::new(0.0, 0.0, 0.0);
+
+        assert_eq!(texture.value(0.0, 1.0, p).x(), 1.0);
+        assert_eq!(texture.value(1.0, 0.0, p).y(), 1.0);
+        assert_eq!(texture.value(-3.0, 2.0, p).x(), 1.0);
+
+        for (width, height) in [(0, 0), (0, 3), (3, 0)] {
+            let empty = ImageTexture::new(Image::new(width, height));
+            assert_eq!(empty.value(0.5, 0.5, p).z(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_load() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color::new(0.25, 0.5, 1.0));
+
+        let path = std::env::temp_dir().join(format!("rtiow-texture-{}.png", std::process::id()));
+        encoder::save(&image, &path, &PngEncoder::new(PngBitDepth::Eight)).unwrap();
+        let texture = ImageTexture::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        // Linear colors survive the gamma round trip, up to 8-bit quantization.
+        let color = texture.unwrap().value(0.0, 0.5, Point3::new(0.0, 0.0, 0.0));
+        assert!(f64::abs(color.x() - 0.25) < 0.01);
+        assert!(f64::abs(color.y() - 0.5) < 0.01);
+        assert!(f64::abs(color.z() - 1.0) < 0.01);
+
+        assert!(ImageTexture::load(Path::new("missing.png")).is_err());
+    }
+}
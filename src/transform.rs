@@ -0,0 +1,436 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::matrix::{Mat4, Motion};
+use crate::ray::Ray;
+use crate::rtweekend::Rng;
+use crate::vec3::{Point3, Vec3};
+
+// Wrappers placing an object in the scene without copying it: rays are moved into the object's
+// space, and hit points and normals moved back out. Wrap an `Arc` to instance a mesh or a
+// `HittableList` several times while sharing its memory.
+
+pub struct Translate<H: Hittable> {
+    object: H,
+    offset: Vec3,
+    bbox: Aabb,
+}
+
+impl<H: Hittable> Translate<H> {
+    pub fn new(object: H, offset: Vec3) -> Self {
+        let bbox = Mat4::translation(offset).transform_bbox(object.bounding_box());
+        Translate {
+            object,
+            offset,
+            bbox,
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for Translate<H> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        // Move the ray backwards by the offset
+        let offset_r =
+            Ray::with_time(r.origin() - self.offset, r.direction(), r.time()).with_seed(r.seed());
+
+        // Determine whether an intersection exists along the offset ray (and if so, where)
+        let mut rec = self.object.hit(offset_r, ray_t)?;
+
+        // Move the intersection point forwards by the offset
+        rec.p += self.offset;
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        self.object.pdf_value(origin - self.offset, direction, time)
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        self.object.random(origin - self.offset, time, rng)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.object.materials(visit)
+    }
+}
+
+// Applies any invertible affine transform, e.g. a product of `Mat4` translations, rotations and
+// scalings. Normals are transformed by the inverse transpose, which keeps them perpendicular to
+// surfaces under non-uniform scaling.
+pub struct Transform<H: Hittable> {
+    object: H,
+    matrix: Mat4,
+    inverse: Mat4,
+    normal_matrix: Mat4,
+    motion: Option<Motion>, // Motion from time 0 to 1 of a moving transform
+    bbox: Aabb,
+}
+
+impl<H: Hittable> Transform<H> {
+    // Panics if `matrix` is not invertible.
+    pub fn new(object: H, matrix: Mat4) -> Self {
+        let inverse = matrix
+            .inverse()
+            .expect("transform matrix is not invertible");
+        let bbox = matrix.transform_bbox(object.bounding_box());
+        Transform {
+            object,
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+            motion: None,
+            bbox,
+        }
+    }
+
+    // Moves from `start` at time 0 to `end` at time 1, see `Motion`. Panics if `start` or `end`
+    // is not invertible, or if only one of them mirrors the object.
+    pub fn moving(object: H, start: Mat4, end: Mat4) -> Self {
+        let mut transform = Transform::new(object, start);
+        let motion = Motion::new(start, end);
+        transform.bbox = motion.transform_bbox(transform.object.bounding_box());
+        transform.motion = Some(motion);
+        transform
+    }
+}
+
+impl<H: Hittable> Transform<H> {
+    // Returns the matrix, its inverse and the normal matrix at `time`.
+    fn matrices(&self, time: f64) -> (Mat4, Mat4, Mat4) {
+        match self.motion {
+            None => (self.matrix, self.inverse, self.normal_matrix),
+            Some(motion) => {
+                let (matrix, inverse) = motion.at_with_inverse(time);
+                (matrix, inverse, inverse.transpose())
+            }
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for Transform<H> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        let (matrix, inverse, normal_matrix) = self.matrices(r.time());
+
+        // The direction is not normalized, so t is the same in both spaces.
+        let object_r = Ray::with_time(
+            inverse.transform_point(r.origin()),
+            inverse.transform_vector(r.direction()),
+            r.time(),
+        )
+        .with_seed(r.seed());
+
+        let mut rec = self.object.hit(object_r, ray_t)?;
+
+        rec.p = matrix.transform_point(rec.p);
+        rec.normal = normal_matrix.transform_vector(rec.normal).unit();
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        // The matrix also changes solid angles: a unit world direction maps to the object
+        // direction v, and a small cone around it to one |det| / |v|^3 as wide.
+        let (_, inverse, _) = self.matrices(time);
+        let v = inverse.transform_vector(direction.unit());
+        let object_pdf = self
+            .object
+            .pdf_value(inverse.transform_point(origin), v, time);
+        object_pdf * f64::abs(inverse.determinant()) / (v.length() * v.length_squared())
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        let (matrix, inverse, _) = self.matrices(time);
+        let direction = self
+            .object
+            .random(inverse.transform_point(origin), time, rng);
+        matrix.transform_vector(direction)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.object.materials(visit)
+    }
+}
+
+// Rotations by an angle in degrees, counterclockwise when looking down the axis towards the
+// origin.
+
+pub struct RotateX<H: Hittable>(Transform<H>);
+
+impl<H: Hittable> RotateX<H> {
+    pub fn new(object: H, angle: f64) -> Self {
+        RotateX(Transform::new(object, Mat4::rotation_x(angle)))
+    }
+}
+
+impl<H: Hittable> Hittable for RotateX<H> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        self.0.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.0.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        self.0.pdf_value(origin, direction, time)
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        self.0.random(origin, time, rng)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.0.materials(visit)
+    }
+}
+
+pub struct RotateY<H: Hittable>(Transform<H>);
+
+impl<H: Hittable> RotateY<H> {
+    pub fn new(object: H, angle: f64) -> Self {
+        RotateY(Transform::new(object, Mat4::rotation_y(angle)))
+    }
+}
+
+impl<H: Hittable> Hittable for RotateY<H> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        self.0.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.0.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        self.0.pdf_value(origin, direction, time)
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        self.0.random(origin, time, rng)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.0.materials(visit)
+    }
+}
+
+pub struct RotateZ<H: Hittable>(Transform<H>);
+
+impl<H: Hittable> RotateZ<H> {
+    pub fn new(object: H, angle: f64) -> Self {
+        RotateZ(Transform::new(object, Mat4::rotation_z(angle)))
+    }
+}
+
+impl<H: Hittable> Hittable for RotateZ<H> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        self.0.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.0.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, time: f64) -> f64 {
+        self.0.pdf_value(origin, direction, time)
+    }
+
+    fn random(&self, origin: Point3, time: f64, rng: &mut Rng) -> Vec3 {
+        self.0.random(origin, time, rng)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        self.0.materials(visit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::hittable_list::HittableList;
+    use crate::interval;
+    use crate::material::Lambertian;
+    use crate::matrix::IDENTITY;
+    use crate::quad::Quad;
+    use crate::rtweekend::seeded_rng;
+    use crate::sphere::Sphere;
+    use std::sync::Arc;
+
+    fn unit_sphere() -> Sphere<Lambertian> {
+        Sphere::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_translate() {
+        let sphere = Translate::new(unit_sphere(), Vec3::new(0.0, 0.0, -5.0));
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = sphere.hit(r, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_eq!(rec.p.z(), -4.0);
+        assert_eq!(rec.normal.z(), 1.0);
+        assert!(f64::abs(sphere.bounding_box().z.max + 4.0) < 1e-9);
+    }
+
+    #[test]
+    fn test_rotate() {
+        // The quad faces +z, after a quarter turn around y it faces +x.
+        let quad = Quad::new(
+            Point3::new(-1.0, -1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let rotated = RotateY::new(quad, 90.0);
+
+        let r = Ray::new(Point3::new(3.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+        let rec = rotated.hit(r, interval::UNIVERSE).unwrap();
+        assert!(f64::abs(rec.t - 3.0) < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((rec.p - Point3::new(0.0, 0.5, 0.5)).length() < 1e-9);
+
+        let r = Ray::new(Point3::new(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(rotated.hit(r, interval::UNIVERSE).is_none());
+    }
+
+    #[test]
+    fn test_transform_normals() {
+        // Squash a sphere into an ellipsoid twice as wide as it is high.
+        let matrix = Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let ellipsoid = Transform::new(unit_sphere(), matrix);
+
+        let r = Ray::new(Point3::new(5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let rec = ellipsoid.hit(r, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 3.0);
+
+        // At 45 degrees on the unit sphere the normal of the ellipsoid leans towards y.
+        let d = Vec3::new(0.0, -1.0, 0.0);
+        let r = Ray::new(Point3::new(f64::sqrt(2.0), 5.0, 0.0), d);
+        let rec = ellipsoid.hit(r, interval::UNIVERSE).unwrap();
+        let expected = Vec3::new(0.5, 1.0, 0.0).unit();
+        assert!((rec.normal - expected).length() < 1e-9, "{:?}", rec.normal);
+        assert!(f64::abs(rec.normal.length() - 1.0) < 1e-9);
+    }
+
+    #[test]
+    fn test_moving_transform() {
+        let start = Mat4::translation(Vec3::new(0.0, 0.0, -5.0));
+        let end = Mat4::translation(Vec3::new(4.0, 0.0, -5.0)) * Mat4::rotation_y(90.0);
+        let sphere = Transform::moving(unit_sphere(), start, end);
+
+        let bbox = sphere.bounding_box();
+        assert!(bbox.x.min <= -1.0 && bbox.x.min > -1.5, "{:?}", bbox);
+        assert!(bbox.x.max >= 5.0 && bbox.x.max < 5.5, "{:?}", bbox);
+
+        let d = Vec3::new(0.0, 0.0, -1.0);
+        for (x, time) in [(0.0, 0.0), (2.0, 0.5), (4.0, 1.0)] {
+            let r = Ray::with_time(Point3::new(x, 0.0, 0.0), d, time);
+            let rec = sphere.hit(r, interval::UNIVERSE).unwrap();
+            assert!((rec.p.z() + 4.0).abs() < 0.3, "{:?}", rec.p);
+            assert!(f64::abs(rec.normal.length() - 1.0) < 1e-9);
+        }
+        let r = Ray::with_time(Point3::new(4.0, 0.0, 0.0), d, 0.0);
+        assert!(sphere.hit(r, interval::UNIVERSE).is_none());
+
+        // A growing sphere has radius 2 halfway.
+        let end = start * Mat4::scaling(Vec3::new(3.0, 3.0, 3.0));
+        let sphere = Transform::moving(unit_sphere(), start, end);
+        let r = Ray::with_time(Point3::new(0.0, 0.0, 0.0), d, 0.5);
+        assert!(f64::abs(sphere.hit(r, interval::UNIVERSE).unwrap().t - 3.0) < 1e-9);
+    }
+
+    // Checks that `transformed` samples like `expected`, the same light built in place.
+    fn assert_same_light(transformed: &dyn Hittable, expected: &dyn Hittable, time: f64) {
+        let mut rng = seeded_rng(0, 0);
+        let origin = Point3::new(0.3, 0.2, 1.0);
+        for _ in 0..100 {
+            let direction = transformed.random(origin, time, &mut rng);
+            let pdf = transformed.pdf_value(origin, direction, time);
+            let expected_pdf = expected.pdf_value(origin, direction, time);
+            assert!(pdf > 0.0);
+            assert!(
+                f64::abs(pdf - expected_pdf) < 1e-9 * expected_pdf,
+                "{} != {}",
+                pdf,
+                expected_pdf
+            );
+        }
+    }
+
+    #[test]
+    fn test_light_sampling() {
+        let quad = |m: Mat4| {
+            Quad::new(
+                m.transform_point(Point3::new(-1.0, -1.0, 0.0)),
+                m.transform_vector(Vec3::new(2.0, 0.0, 0.0)),
+                m.transform_vector(Vec3::new(0.0, 2.0, 0.0)),
+                Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            )
+        };
+        let start = Mat4::translation(Vec3::new(0.0, 1.0, -4.0))
+            * Mat4::rotation_x(30.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 1.0));
+        let end = Mat4::translation(Vec3::new(1.0, 0.0, -3.0)) * Mat4::rotation_z(60.0);
+        assert_same_light(&Transform::new(quad(IDENTITY), start), &quad(start), 0.0);
+        assert_same_light(
+            &Transform::moving(quad(IDENTITY), start, end),
+            &quad(end),
+            1.0,
+        );
+        assert_same_light(
+            &RotateY::new(quad(IDENTITY), 90.0),
+            &quad(Mat4::rotation_y(90.0)),
+            0.0,
+        );
+
+        let sphere = |center: Point3, radius: f64| {
+            Sphere::new(center, radius, Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+        };
+        let offset = Vec3::new(0.5, 0.0, -3.0);
+        assert_same_light(
+            &Translate::new(unit_sphere(), offset),
+            &sphere(offset, 1.0),
+            0.0,
+        );
+        assert_same_light(
+            &Transform::new(unit_sphere(), Mat4::scaling(Vec3::new(3.0, 3.0, 3.0))),
+            &sphere(Point3::new(0.0, 0.0, 0.0), 3.0),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn test_instancing() {
+        let mut group = HittableList::new();
+        group.add(unit_sphere());
+        let group = Arc::new(group);
+
+        let mut world = HittableList::new();
+        for x in [-3.0, 0.0, 3.0] {
+            world.add(Translate::new(group.clone(), Vec3::new(x, 0.0, 0.0)));
+        }
+        assert_eq!(Arc::strong_count(&group), 4);
+
+        for x in [-3.0, 0.0, 3.0] {
+            let r = Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+            assert_eq!(world.hit(r, interval::UNIVERSE).unwrap().t, 4.0);
+        }
+        let bbox = world.bounding_box();
+        assert!(f64::abs(bbox.x.min + 4.0) < 1e-9 && f64::abs(bbox.x.max - 4.0) < 1e-9);
+    }
+}
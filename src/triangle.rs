@@ -0,0 +1,145 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::plane::PlaneBasis;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+// Triangle with vertices a, b and c; the front face is the one where they turn counterclockwise.
+// Per-vertex normals and surface coordinates, when given, are interpolated across the face.
+pub struct Triangle<M: Material> {
+    basis: PlaneBasis,
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[(f64, f64); 3]>,
+    pub mat: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Triangle<M> {
+    pub fn new(a: Point3, b: Point3, c: Point3, mat: M) -> Self {
+        Triangle {
+            basis: PlaneBasis::new(a, b - a, c - a),
+            normals: None,
+            uvs: None,
+            mat,
+            bbox: Aabb::surrounding(Aabb::from_points(a, b), Aabb::from_points(a, c)),
+        }
+    }
+
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl<M: Material> Hittable for Triangle<M> {
+    fn hit(&self, r: Ray, ray_t: Interval) -> Option<HitRecord<'_>> {
+        let (t, alpha, beta) = self.basis.hit(r, ray_t)?;
+
+        // The plane coordinates are the barycentric weights of b and c.
+        if alpha < 0.0 || beta < 0.0 || alpha + beta > 1.0 {
+            return None;
+        }
+        let weights = [1.0 - alpha - beta, alpha, beta];
+
+        let (u, v) = match self.uvs {
+            Some(uvs) => (
+                weights[0] * uvs[0].0 + weights[1] * uvs[1].0 + weights[2] * uvs[2].0,
+                weights[0] * uvs[0].1 + weights[1] * uvs[1].1 + weights[2] * uvs[2].1,
+            ),
+            None => (alpha, beta),
+        };
+
+        let mut rec = HitRecord {
+            t,
+            p: r.at(t),
+            normal: self.basis.normal,
+            u,
+            v,
+            front_face: false,
+            material: &self.mat,
+        };
+
+        // The geometric normal decides which side was hit, the shading normal is flipped to
+        // match it.
+        rec.set_face_normal(r, self.basis.normal);
+        if let Some(normals) = self.normals {
+            let shading_normal =
+                (weights[0] * normals[0] + weights[1] * normals[1] + weights[2] * normals[2])
+                    .unit();
+            rec.normal = if rec.front_face {
+                shading_normal
+            } else {
+                -shading_normal
+            };
+        }
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&dyn Material)) {
+        visit(&self.mat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::interval;
+    use crate::material::Lambertian;
+
+    fn triangle() -> Triangle<Lambertian> {
+        Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_triangle() {
+        let triangle = triangle();
+
+        let r = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = triangle.hit(r, interval::UNIVERSE).unwrap();
+        assert_eq!(rec.t, 1.0);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal.z(), 1.0);
+        assert_eq!((rec.u, rec.v), (0.25, 0.5));
+
+        // Inside the bounding square, outside the triangle.
+        let r = Ray::new(Point3::new(0.75, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(triangle.hit(r, interval::UNIVERSE).is_none());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let triangle = triangle()
+            .with_normals([
+                Vec3::new(0.0, 0.0, 1.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+            ])
+            .with_uvs([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+
+        let r = Ray::new(Point3::new(0.5, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = triangle.hit(r, interval::UNIVERSE).unwrap();
+        assert!(!rec.front_face);
+        assert_eq!((rec.u, rec.v), (0.5, 0.0));
+
+        let expected = -Vec3::new(0.5, 0.0, 0.5).unit();
+        assert!((rec.normal - expected).length() < 1e-9, "{:?}", rec.normal);
+    }
+}
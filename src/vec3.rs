@@ -1,7 +1,11 @@
-use std::{cmp::min, ops};
+use std::f64;
+use std::ops;
 
-use crate::rtweekend::{random_f64, random_f64_bounded};
-#[derive(Copy, Clone, Default, Debug)]
+use serde::Deserialize;
+
+use crate::rtweekend::{random_f64, random_f64_bounded, Rng};
+#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[serde(from = "[f64; 3]")]
 pub struct Vec3 {
     e: [f64; 3],
 }
@@ -27,12 +31,12 @@ impl Vec3 {
         self / self.length()
     }
 
-    pub fn random_unit() -> Vec3 {
+    pub fn random_unit(rng: &mut Rng) -> Vec3 {
         let mut p;
         let mut lensq;
 
         loop {
-            p = Vec3::random_bounded(-1.0, 1.0);
+            p = Vec3::random_bounded(rng, -1.0, 1.0);
             lensq = p.length_squared();
             if 1e-160 < lensq && lensq <= 1.0 {
                 break;
@@ -61,22 +65,22 @@ impl Vec3 {
         )
     }
 
-    pub fn random() -> Vec3 {
+    pub fn random(rng: &mut Rng) -> Vec3 {
         Vec3 {
-            e: [random_f64(), random_f64(), random_f64()],
+            e: [random_f64(rng), random_f64(rng), random_f64(rng)],
         }
     }
-    pub fn random_bounded(min: f64, max: f64) -> Vec3 {
+    pub fn random_bounded(rng: &mut Rng, min: f64, max: f64) -> Vec3 {
         Vec3 {
             e: [
-                random_f64_bounded(min, max),
-                random_f64_bounded(min, max),
-                random_f64_bounded(min, max),
+                random_f64_bounded(rng, min, max),
+                random_f64_bounded(rng, min, max),
+                random_f64_bounded(rng, min, max),
             ],
         }
     }
-    pub fn random_on_hemisphere(normal: Vec3) -> Vec3 {
-        let on_unit_sphere: Vec3 = Vec3::random_unit();
+    pub fn random_on_hemisphere(rng: &mut Rng, normal: Vec3) -> Vec3 {
+        let on_unit_sphere: Vec3 = Vec3::random_unit(rng);
         if on_unit_sphere.dot(normal) > 0.0 {
             // In the same hemisphere as the normal
             return on_unit_sphere;
@@ -84,15 +88,32 @@ impl Vec3 {
         -on_unit_sphere
     }
 
-    pub fn random_in_unit_disk() -> Vec3 {
+    pub fn random_cosine_direction(rng: &mut Rng) -> Vec3 {
+        // Returns a direction on the +z hemisphere, with a density proportional to cos(theta).
+        let r1 = random_f64(rng);
+        let r2 = random_f64(rng);
+
+        let phi = 2.0 * f64::consts::PI * r1;
+        let x = f64::cos(phi) * f64::sqrt(r2);
+        let y = f64::sin(phi) * f64::sqrt(r2);
+        let z = f64::sqrt(1.0 - r2);
+
+        Vec3::new(x, y, z)
+    }
+
+    pub fn random_in_unit_disk(rng: &mut Rng) -> Vec3 {
         let mut ret: Vec3;
         loop {
-            ret = Vec3::new(random_f64(), random_f64(), 0.0);
+            ret = Vec3::new(
+                random_f64_bounded(rng, -1.0, 1.0),
+                random_f64_bounded(rng, -1.0, 1.0),
+                0.0,
+            );
             if ret.length_squared() < 1.0 {
                 break;
             }
         }
-        return ret;
+        ret
     }
 
     pub fn near_zero(self) -> bool {
@@ -110,9 +131,15 @@ impl Vec3 {
 
         let r_out_perp = etai_over_etat * (self + cos_theta * n);
         let r_out_parallel = -f64::sqrt(f64::abs(1.0 - r_out_perp.length_squared())) * n;
-        return r_out_perp + r_out_parallel;
+        r_out_perp + r_out_parallel
+    }
+}
+impl From<[f64; 3]> for Vec3 {
+    fn from(e: [f64; 3]) -> Vec3 {
+        Vec3 { e }
     }
 }
+
 impl ops::Add<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -259,6 +286,7 @@ impl ops::Neg for Vec3 {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::rtweekend::seeded_rng;
 
     #[test]
     fn test_new() {
@@ -385,6 +413,20 @@ mod tests {
         assert_eq!(v1.length(), f64::sqrt(14.0));
     }
 
+    #[test]
+    fn test_random_in_unit_disk() {
+        let mut rng = seeded_rng(0, 0);
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..1000 {
+            let p = Vec3::random_in_unit_disk(&mut rng);
+            assert!(p.length_squared() < 1.0);
+            assert_eq!(p.z(), 0.0);
+            sum += p;
+        }
+        // Samples cover the whole disk, not a single quadrant.
+        assert!((sum / 1000.0).length() < 0.1);
+    }
+
     #[test]
     fn test_operators() {
         let v1 = Vec3::new(1.0, 2.0, 3.0);
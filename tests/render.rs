@@ -0,0 +1,184 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use rtiow::bvh::BvhNode;
+use rtiow::camera::Camera;
+use rtiow::color::Color;
+use rtiow::encoder::{Encoder, PngBitDepth, PngEncoder, PpmEncoder, PpmFormat};
+use rtiow::hittable::Hittable;
+use rtiow::hittable_list::HittableList;
+use rtiow::image::Image;
+use rtiow::material::{Dielectric, Lambertian, Metal};
+use rtiow::matrix::Mat4;
+use rtiow::mesh::{MeshMaterials, TriangleMesh};
+use rtiow::scene::{self, CameraSettings};
+use rtiow::scene_file;
+use rtiow::sphere::Sphere;
+use rtiow::transform::RotateY;
+use rtiow::vec3::{Point3, Vec3};
+
+fn world() -> HittableList {
+    let mut world = HittableList::new();
+    world.add(Sphere::new(
+        Point3::new(0.0, -100.5, -1.0),
+        100.0,
+        Lambertian::new(Color::new(0.8, 0.8, 0.0)),
+    ));
+    world.add(Sphere::new(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.5,
+        Dielectric::new(1.5),
+    ));
+    world.add(Sphere::new(
+        Point3::new(1.0, 0.0, -1.0),
+        0.5,
+        Metal::new(Color::new(0.8, 0.6, 0.2), 0.1),
+    ));
+    world
+}
+
+fn camera() -> Camera {
+    let mut camera = Camera::new(
+        32,
+        16.0 / 9.0,
+        8,
+        10,
+        90,
+        0.0,
+        1.0,
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 0.0, -1.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    camera.threads = 2;
+    camera
+}
+
+#[test]
+fn render_world() {
+    let image = camera().render(Arc::new(BvhNode::new(world())));
+
+    assert_eq!(image.width(), 32);
+    assert_eq!(image.height(), 18);
+
+    // The top row sees the sky, which is never black.
+    assert!(image.rows().next().unwrap().iter().all(|c| c.z() > 0.5));
+}
+
+#[test]
+fn bvh_matches_list() {
+    let camera = camera();
+    let from_list = camera.render(Arc::new(world()));
+    let from_bvh = camera.render(Arc::new(BvhNode::new(world())));
+
+    assert_eq!(
+        format!("{:?}", from_list.pixels()),
+        format!("{:?}", from_bvh.pixels())
+    );
+}
+
+#[test]
+fn encode_rendered_image() {
+    let image = camera().render(Arc::new(world()));
+
+    let mut ppm = Vec::new();
+    PpmEncoder::new(PpmFormat::Binary)
+        .encode(&image, &mut ppm)
+        .unwrap();
+    assert!(ppm.starts_with(b"P6\n32 18\n255\n"));
+    assert_eq!(ppm.len(), b"P6\n32 18\n255\n".len() + 32 * 18 * 3);
+
+    let mut png = Vec::new();
+    PngEncoder::new(PngBitDepth::Eight)
+        .encode(&image, &mut png)
+        .unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn render_scene_files() {
+    for name in ["three-spheres.toml", "mesh.toml"] {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("scenes")
+            .join(name);
+        let scene = scene_file::load(&path).unwrap();
+
+        let settings = CameraSettings {
+            image_width: 16,
+            samples_per_pixel: 2,
+            ..scene.camera
+        };
+        let image = settings.build().render(Arc::new(scene.world));
+        assert_eq!(image.width(), 16);
+    }
+}
+
+#[test]
+fn builtin_scenes() {
+    for name in scene::BUILTIN_SCENES {
+        assert!(scene::builtin(name, 0).is_some(), "{}", name);
+    }
+    assert!(scene::builtin("missing", 0).is_none());
+}
+
+#[test]
+fn rotated_vertex_colors() {
+    // A red, green and blue triangle under a white sky: every pixel shows the blended albedo.
+    let ply = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+               property float z\nproperty uchar red\nproperty uchar green\nproperty uchar blue\n\
+               element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+               -1 -1 0 255 0 0\n1 -1 0 0 255 0\n0 1 0 0 0 255\n3 0 1 2\n";
+    let mesh = || {
+        let materials = MeshMaterials::new(Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+            .with_vertex_colors();
+        TriangleMesh::parse_ply(ply.as_bytes(), &materials).unwrap()
+    };
+    fn render<H: Hittable + 'static>(object: H, lookfrom: Point3) -> Image {
+        let mut camera = Camera::new(
+            16,
+            1.0,
+            1,
+            2,
+            40,
+            0.0,
+            5.0,
+            lookfrom,
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        camera.background = Some(Color::new(1.0, 1.0, 1.0));
+        camera.threads = 1;
+        camera.render(Arc::new(object))
+    }
+
+    // Rotating the triangle and the camera together leaves the image unchanged.
+    let front = render(mesh(), Point3::new(0.0, 0.0, 5.0));
+    let rotated = render(
+        RotateY::new(mesh(), 90.0),
+        Mat4::rotation_y(90.0).transform_point(Point3::new(0.0, 0.0, 5.0)),
+    );
+
+    let mut covered = 0;
+    for (front, rotated) in front.rows().zip(rotated.rows()) {
+        for (&front, &rotated) in front.iter().zip(rotated) {
+            assert!(
+                (front - rotated).length() < 1e-6,
+                "{:?} {:?}",
+                front,
+                rotated
+            );
+            if front.x() + front.y() + front.z() < 1.5 {
+                // Barycentric blends of primaries, inside the triangle.
+                assert!(f64::abs(front.x() + front.y() + front.z() - 1.0) < 1e-6);
+                covered += 1;
+            }
+        }
+    }
+    assert!(covered > 16);
+
+    // Each corner of the triangle shows its own color.
+    let pixel = |x: usize, y: usize| front.rows().nth(y).unwrap()[x];
+    assert!(pixel(8, 5).z() > 0.5, "{:?}", pixel(8, 5)); // top, blue
+    assert!(pixel(4, 12).x() > 0.5, "{:?}", pixel(4, 12)); // bottom left, red
+    assert!(pixel(11, 12).y() > 0.5, "{:?}", pixel(11, 12)); // bottom right, green
+}